@@ -0,0 +1,156 @@
+// bool-per-pixel screen (what `Emu` uses today) against a bitset with one u64 per row
+// the two standalone models do the same CLS / DRAW / readback work so their numbers compare the
+// representations alone; the `emu` entries show how much of a real frame that work is
+//
+//   cargo bench --features asm
+
+use chip8_core::asm::{add_vx_nn, cls, drw, jmp, ld_f_vx, ld_vx_nn};
+use chip8_core::{Emu, SCREEN_HEIGHT, SCREEN_WIDTH};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// instructions per 60 Hz frame at the desktop's default 600 Hz
+const TICKS_PER_FRAME: usize = 10;
+
+// the font sprite for 'A'; any 5 row sprite would do
+const SPRITE: [u8; 5] = [0xF0, 0x90, 0xF0, 0x90, 0x90];
+
+// one bool per pixel, row by row
+struct BoolScreen([bool; SCREEN_WIDTH * SCREEN_HEIGHT]);
+
+impl BoolScreen {
+    fn new() -> Self {
+        Self([false; SCREEN_WIDTH * SCREEN_HEIGHT])
+    }
+
+    fn clear(&mut self) {
+        self.0 = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+    }
+
+    // XOR an 8 pixel wide sprite in, wrapping at the edges; returns whether anything collided
+    fn draw(&mut self, x: usize, y: usize, sprite: &[u8]) -> bool {
+        let mut collided = false;
+        for (row, byte) in sprite.iter().enumerate() {
+            for col in 0..8 {
+                if byte & (0x80 >> col) != 0 {
+                    let index = (x + col) % SCREEN_WIDTH + (y + row) % SCREEN_HEIGHT * SCREEN_WIDTH;
+                    collided |= self.0[index];
+                    self.0[index] = !self.0[index];
+                }
+            }
+        }
+        collided
+    }
+
+    fn lit(&self) -> usize {
+        self.0.iter().filter(|pixel| **pixel).count()
+    }
+}
+
+// one u64 per row with x = 0 in the top bit, so a sprite row is a single shift, test and XOR
+struct BitScreen([u64; SCREEN_HEIGHT]);
+
+impl BitScreen {
+    fn new() -> Self {
+        Self([0; SCREEN_HEIGHT])
+    }
+
+    fn clear(&mut self) {
+        self.0 = [0; SCREEN_HEIGHT];
+    }
+
+    fn draw(&mut self, x: usize, y: usize, sprite: &[u8]) -> bool {
+        let mut collided = false;
+        for (row, byte) in sprite.iter().enumerate() {
+            // rotating instead of shifting wraps the sprite around the right edge
+            let bits = ((*byte as u64) << 56).rotate_right(x as u32 % 64);
+            let line = &mut self.0[(y + row) % SCREEN_HEIGHT];
+            collided |= *line & bits != 0;
+            *line ^= bits;
+        }
+        collided
+    }
+
+    fn lit(&self) -> usize {
+        self.0.iter().map(|row| row.count_ones() as usize).sum()
+    }
+}
+
+// draws the 'A' font sprite over and over, moving it a little each time
+fn draw_rom() -> Vec<u8> {
+    [
+        ld_vx_nn(2, 0xA),
+        ld_f_vx(2),
+        // 0x204: the loop
+        drw(0, 1, 5),
+        add_vx_nn(0, 3),
+        add_vx_nn(1, 1),
+        jmp(0x204),
+    ]
+    .concat()
+}
+
+fn emu_with(rom: &[u8]) -> Emu {
+    let mut emu = Emu::new();
+    emu.load(rom).unwrap();
+    emu
+}
+
+fn bench_cls(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cls");
+    let mut bools = BoolScreen::new();
+    group.bench_function("bool", |b| b.iter(|| black_box(&mut bools).clear()));
+    let mut bits = BitScreen::new();
+    group.bench_function("bitset", |b| b.iter(|| black_box(&mut bits).clear()));
+    let mut emu = emu_with(&[cls(), jmp(0x200)].concat());
+    group.bench_function("emu", |b| b.iter(|| emu.run_cycles(2).unwrap()));
+    group.finish();
+}
+
+fn bench_draw(c: &mut Criterion) {
+    // the same walk across the screen the ROM does
+    let mut group = c.benchmark_group("draw");
+    let mut bools = BoolScreen::new();
+    let mut step = 0;
+    group.bench_function("bool", |b| {
+        b.iter(|| {
+            step += 1;
+            bools.draw(step * 3, step, black_box(&SPRITE))
+        })
+    });
+    let mut bits = BitScreen::new();
+    let mut step = 0;
+    group.bench_function("bitset", |b| {
+        b.iter(|| {
+            step += 1;
+            bits.draw(step * 3, step, black_box(&SPRITE))
+        })
+    });
+    let mut emu = emu_with(&draw_rom());
+    group.bench_function("emu frame", |b| {
+        b.iter(|| emu.run_frame(TICKS_PER_FRAME).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_get_display(c: &mut Criterion) {
+    // what a frontend does every frame: walk the whole screen
+    let mut group = c.benchmark_group("get_display");
+    let mut bools = BoolScreen::new();
+    let mut bits = BitScreen::new();
+    let mut emu = emu_with(&draw_rom());
+    for step in 0..100 {
+        bools.draw(step * 3, step, &SPRITE);
+        bits.draw(step * 3, step, &SPRITE);
+    }
+    emu.run_cycles(300).unwrap();
+
+    group.bench_function("bool", |b| b.iter(|| black_box(&bools).lit()));
+    group.bench_function("bitset", |b| b.iter(|| black_box(&bits).lit()));
+    group.bench_function("emu", |b| {
+        b.iter(|| emu.get_display().iter().filter(|pixel| **pixel).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_cls, bench_draw, bench_get_display);
+criterion_main!(benches);
@@ -0,0 +1,151 @@
+use alloc::vec::Vec;
+
+use crate::Decoded;
+
+// a tiny assembler for building ROMs in code (tests, examples) instead of writing opcode bytes by hand
+// `assemble` takes whole instructions; the short helpers below cover the common ones and return the two
+// big-endian bytes of the opcode, ready to be chained together:
+//   [ld_vx_nn(0, 28), ld_i(0x20C), drw(0, 1, 5), jmp(0x206)].concat()
+// register indices and immediates are masked to their field widths, so out of range values don't bleed
+// into the neighbouring fields
+
+impl Decoded {
+    // the opcode for this instruction; `decode(instr.encode()) == Some(instr)` for every in-range instruction
+    pub fn encode(&self) -> u16 {
+        // place VX, VY and N in their nibbles
+        let xy = |x: u8, y: u8| ((x as u16 & 0xF) << 8) | ((y as u16 & 0xF) << 4);
+        let xnn = |x: u8, nn: u8| ((x as u16 & 0xF) << 8) | nn as u16;
+        let x = |x: u8| (x as u16 & 0xF) << 8;
+
+        match *self {
+            Decoded::Nop => 0x0000,
+            Decoded::Cls => 0x00E0,
+            Decoded::Ret => 0x00EE,
+            Decoded::ScrollDown { n } => 0x00C0 | (n as u16 & 0xF),
+            Decoded::ScrollRight => 0x00FB,
+            Decoded::ScrollLeft => 0x00FC,
+            Decoded::Lores => 0x00FE,
+            Decoded::Hires => 0x00FF,
+            Decoded::Jmp { nnn } => 0x1000 | (nnn & 0xFFF),
+            Decoded::Call { nnn } => 0x2000 | (nnn & 0xFFF),
+            Decoded::SkipEqNN { x, nn } => 0x3000 | xnn(x, nn),
+            Decoded::SkipNeNN { x, nn } => 0x4000 | xnn(x, nn),
+            Decoded::SkipEqVY { x, y } => 0x5000 | xy(x, y),
+            Decoded::SetNN { x, nn } => 0x6000 | xnn(x, nn),
+            Decoded::AddNN { x, nn } => 0x7000 | xnn(x, nn),
+            Decoded::Set { x, y } => 0x8000 | xy(x, y),
+            Decoded::Or { x, y } => 0x8001 | xy(x, y),
+            Decoded::And { x, y } => 0x8002 | xy(x, y),
+            Decoded::Xor { x, y } => 0x8003 | xy(x, y),
+            Decoded::Add { x, y } => 0x8004 | xy(x, y),
+            Decoded::Sub { x, y } => 0x8005 | xy(x, y),
+            Decoded::Shr { x, y } => 0x8006 | xy(x, y),
+            Decoded::SubN { x, y } => 0x8007 | xy(x, y),
+            Decoded::Shl { x, y } => 0x800E | xy(x, y),
+            Decoded::SkipNeVY { x, y } => 0x9000 | xy(x, y),
+            Decoded::SetI { nnn } => 0xA000 | (nnn & 0xFFF),
+            Decoded::JmpV0 { nnn } => 0xB000 | (nnn & 0xFFF),
+            Decoded::Rand { x, nn } => 0xC000 | xnn(x, nn),
+            Decoded::Draw { x, y, n } => 0xD000 | xy(x, y) | (n as u16 & 0xF),
+            Decoded::SkipKey { x: vx } => 0xE09E | x(vx),
+            Decoded::SkipNotKey { x: vx } => 0xE0A1 | x(vx),
+            Decoded::GetDelay { x: vx } => 0xF007 | x(vx),
+            Decoded::WaitKey { x: vx } => 0xF00A | x(vx),
+            Decoded::SetDelay { x: vx } => 0xF015 | x(vx),
+            Decoded::SetSound { x: vx } => 0xF018 | x(vx),
+            Decoded::AddI { x: vx } => 0xF01E | x(vx),
+            Decoded::Font { x: vx } => 0xF029 | x(vx),
+            Decoded::Bcd { x: vx } => 0xF033 | x(vx),
+            Decoded::Store { x: vx } => 0xF055 | x(vx),
+            Decoded::Load { x: vx } => 0xF065 | x(vx),
+            Decoded::Plane { n } => 0xF001 | x(n),
+            Decoded::SpriteHeight { x: vx } => 0xF01D | x(vx),
+            Decoded::SaveFlags { x: vx } => 0xF075 | x(vx),
+            Decoded::LoadFlags { x: vx } => 0xF085 | x(vx),
+            Decoded::StoreRange { x, y } => 0x5002 | xy(x, y),
+            Decoded::LoadRange { x, y } => 0x5003 | xy(x, y),
+        }
+    }
+}
+
+// a ROM made of `instrs` in order, ready for `Emu::load`
+pub fn assemble(instrs: &[Decoded]) -> Vec<u8> {
+    instrs
+        .iter()
+        .flat_map(|instr| instr.encode().to_be_bytes())
+        .collect()
+}
+
+fn bytes(instr: Decoded) -> [u8; 2] {
+    instr.encode().to_be_bytes()
+}
+
+// 00E0
+pub fn cls() -> [u8; 2] {
+    bytes(Decoded::Cls)
+}
+
+// 00EE
+pub fn ret() -> [u8; 2] {
+    bytes(Decoded::Ret)
+}
+
+// 1NNN; jumping to its own address is the usual way to halt
+pub fn jmp(addr: u16) -> [u8; 2] {
+    bytes(Decoded::Jmp { nnn: addr })
+}
+
+// 2NNN
+pub fn call(addr: u16) -> [u8; 2] {
+    bytes(Decoded::Call { nnn: addr })
+}
+
+// 3XNN
+pub fn se_vx_nn(x: u8, nn: u8) -> [u8; 2] {
+    bytes(Decoded::SkipEqNN { x, nn })
+}
+
+// 4XNN
+pub fn sne_vx_nn(x: u8, nn: u8) -> [u8; 2] {
+    bytes(Decoded::SkipNeNN { x, nn })
+}
+
+// 6XNN
+pub fn ld_vx_nn(x: u8, nn: u8) -> [u8; 2] {
+    bytes(Decoded::SetNN { x, nn })
+}
+
+// 7XNN
+pub fn add_vx_nn(x: u8, nn: u8) -> [u8; 2] {
+    bytes(Decoded::AddNN { x, nn })
+}
+
+// 8XY0
+pub fn ld_vx_vy(x: u8, y: u8) -> [u8; 2] {
+    bytes(Decoded::Set { x, y })
+}
+
+// ANNN
+pub fn ld_i(addr: u16) -> [u8; 2] {
+    bytes(Decoded::SetI { nnn: addr })
+}
+
+// DXYN
+pub fn drw(x: u8, y: u8, n: u8) -> [u8; 2] {
+    bytes(Decoded::Draw { x, y, n })
+}
+
+// FX0A
+pub fn wait_key(x: u8) -> [u8; 2] {
+    bytes(Decoded::WaitKey { x })
+}
+
+// FX15
+pub fn ld_dt_vx(x: u8) -> [u8; 2] {
+    bytes(Decoded::SetDelay { x })
+}
+
+// FX29
+pub fn ld_f_vx(x: u8) -> [u8; 2] {
+    bytes(Decoded::Font { x })
+}
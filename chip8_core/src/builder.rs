@@ -0,0 +1,119 @@
+use crate::{Emu, Quirks, Variant, MAX_STACK_SIZE, NUM_OPCODES};
+
+// configures an `Emu` before it's created
+// `EmuBuilder::new().build()` gives the same machine as `Emu::new()`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EmuBuilder {
+    variant: Variant,
+    quirks: Option<Quirks>,
+    clock_hz: u32,
+    histogram: bool,
+    stack_size: Option<usize>,
+    rng_seed: Option<u32>,
+    randomize_ram: bool,
+    plane_count: Option<usize>,
+    costs: Option<[u8; NUM_OPCODES]>,
+    protect_low_ram: bool,
+}
+
+impl EmuBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // instruction set to run (see `Emu::set_variant`)
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    // interpreter quirks, e.g. `Quirks::cosmac_vip()` (see `Emu::set_quirks`)
+    // defaults to the variant's preset
+    pub fn quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = Some(quirks);
+        self
+    }
+
+    // tick the timers automatically (see `Emu::new_with_clock_hz`)
+    pub fn clock_hz(mut self, hz: u32) -> Self {
+        self.clock_hz = hz;
+        self
+    }
+
+    // count how often each instruction executes (see `Emu::opcode_histogram`)
+    pub fn histogram(mut self, enabled: bool) -> Self {
+        self.histogram = enabled;
+        self
+    }
+
+    // how many nested CALLs fit before a stack overflow (default 16)
+    // clamped to 1..=`MAX_STACK_SIZE`
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    // use the built-in generator for CXNN, starting from `seed` (see `Emu::seed_rng`)
+    // also seeds `randomize_ram`
+    pub fn rng_seed(mut self, seed: u32) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
+    // start with random bytes in RAM instead of zeroes, to shake out ROMs that read memory they never wrote
+    // the fontset is kept and `load` writes the program over the garbage as usual; `reset` zeroes RAM again
+    // the bytes come from the CXNN generator: the same for a given `rng_seed`, different every run with
+    // `rand` and no seed, and always the same without either
+    pub fn randomize_ram(mut self, enabled: bool) -> Self {
+        self.randomize_ram = enabled;
+        self
+    }
+
+    // display planes for XO-CHIP forks with more than 2 (see `Emu::set_plane_count`)
+    pub fn plane_count(mut self, count: usize) -> Self {
+        self.plane_count = Some(count);
+        self
+    }
+
+    // charge each instruction its entry in `costs` (indexed like `supported_opcodes()`) against
+    // `run_frame`'s budget instead of 1, e.g. `weighted_costs()` for slower DRAW and CLS
+    // costs of 0 count as 1; without this every instruction costs 1, which is the old fixed count
+    pub fn instruction_costs(mut self, costs: [u8; NUM_OPCODES]) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+
+    // make writes below 0x200 an error (see `Emu::set_protect_low_ram`)
+    pub fn protect_low_ram(mut self, enabled: bool) -> Self {
+        self.protect_low_ram = enabled;
+        self
+    }
+
+    pub fn build(self) -> Emu {
+        let mut emu = Emu::new();
+        emu.set_variant(self.variant);
+        if let Some(quirks) = self.quirks {
+            emu.set_quirks(quirks);
+        }
+        emu.set_clock_hz(self.clock_hz);
+        if self.histogram {
+            emu.histogram = Some([0; NUM_OPCODES]);
+        }
+        emu.costs = self.costs;
+        emu.set_protect_low_ram(self.protect_low_ram);
+        if let Some(size) = self.stack_size {
+            emu.stack_size = size.clamp(1, MAX_STACK_SIZE);
+        }
+        if let Some(count) = self.plane_count {
+            emu.set_plane_count(count);
+        }
+        if let Some(seed) = self.rng_seed {
+            emu.seed_rng(seed);
+        }
+        if self.randomize_ram {
+            emu.randomize_ram();
+        }
+
+        emu
+    }
+}
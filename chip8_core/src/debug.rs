@@ -0,0 +1,258 @@
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+
+use crate::{Emu, ExecError, NUM_REGS, RAM_SIZE};
+
+// breakpoints and watchpoints live in fixed arrays so debugging works without an allocator
+pub(crate) const MAX_BREAKPOINTS: usize = 16;
+pub(crate) const MAX_WATCHPOINTS: usize = 8;
+
+// safety net so stepping over a subroutine that never returns doesn't hang the debugger
+const STEP_OVER_LIMIT: u64 = 1_000_000;
+
+// why `run` handed control back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    // pc reached a breakpoint (the instruction there hasn't run yet)
+    Breakpoint(u16),
+    // an instruction wrote to this address inside a watched range (the write has already happened)
+    Watchpoint(u16),
+    // `max_cycles` instructions ran without hitting anything
+    CycleLimit,
+}
+
+// things worth telling a debugger about as they happen (see `Emu::set_event_hook`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugEvent {
+    // 7XNN wrapped past 0xFF; `value` is VX before the add (only with the `debug_checks` feature)
+    AddOverflow { pc: u16, x: u8, value: u8, nn: u8 },
+    // a DRAW at screen position (`x`, `y`) left VF non-zero: the sprite hit lit pixels
+    // `vf` is what it was set to (a row count in SUPER-CHIP hires mode, otherwise 1)
+    Collision { pc: u16, x: u8, y: u8, vf: u8 },
+    // an instruction is about to be fetched from an odd address, so it straddles two of the ROM's opcodes
+    // legal, but usually a bad jump or self-modifying code gone wrong (see `Emu::set_warn_on_misaligned_pc`)
+    MisalignedPc { pc: u16 },
+    // a CALL pushed return address `addr`, leaving the stack `sp` entries deep (see `Emu::set_trace_stack`)
+    StackPush { pc: u16, addr: u16, sp: u16 },
+    // a RET popped `addr` to return to, leaving the stack `sp` entries deep
+    StackPop { pc: u16, addr: u16, sp: u16 },
+}
+
+// callback installed with `Emu::set_event_hook`
+#[cfg(feature = "alloc")]
+pub type EventHook = Box<dyn FnMut(DebugEvent)>;
+
+// debugger support: breakpoints, running until one is hit, and stepping
+impl Emu {
+    // stop `run` before executing the instruction at `addr`
+    // returns false if the breakpoint table is full
+    pub fn add_breakpoint(&mut self, addr: u16) -> bool {
+        if self.has_breakpoint(addr) {
+            return true;
+        }
+        if self.num_breakpoints == MAX_BREAKPOINTS {
+            return false;
+        }
+
+        self.breakpoints[self.num_breakpoints] = addr;
+        self.num_breakpoints += 1;
+        true
+    }
+
+    // returns false if there was no breakpoint at `addr`
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        let active = &self.breakpoints[..self.num_breakpoints];
+        match active.iter().position(|bp| *bp == addr) {
+            Some(i) => {
+                // move the last breakpoint into the hole; order doesn't matter
+                self.num_breakpoints -= 1;
+                self.breakpoints[i] = self.breakpoints[self.num_breakpoints];
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_breakpoint(&self, addr: u16) -> bool {
+        self.breakpoints[..self.num_breakpoints].contains(&addr)
+    }
+
+    // every active breakpoint, in no particular order
+    pub fn breakpoints(&self) -> &[u16] {
+        &self.breakpoints[..self.num_breakpoints]
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.num_breakpoints = 0;
+    }
+
+    // stop `run` after FX55 or BCD (FX33) writes anywhere in `start..end`
+    // handy for catching self-modifying code; returns false if the range is empty or the table is full
+    pub fn watch_range(&mut self, start: u16, end: u16) -> bool {
+        if start >= end {
+            return false;
+        }
+        if self.watchpoints[..self.num_watchpoints].contains(&(start, end)) {
+            return true;
+        }
+        if self.num_watchpoints == MAX_WATCHPOINTS {
+            return false;
+        }
+
+        self.watchpoints[self.num_watchpoints] = (start, end);
+        self.num_watchpoints += 1;
+        true
+    }
+
+    // returns false if `start..end` wasn't being watched
+    pub fn unwatch_range(&mut self, start: u16, end: u16) -> bool {
+        let active = &self.watchpoints[..self.num_watchpoints];
+        match active.iter().position(|range| *range == (start, end)) {
+            Some(i) => {
+                self.num_watchpoints -= 1;
+                self.watchpoints[i] = self.watchpoints[self.num_watchpoints];
+                true
+            }
+            None => false,
+        }
+    }
+
+    // every watched `(start, end)` range, in no particular order
+    pub fn watchpoints(&self) -> &[(u16, u16)] {
+        &self.watchpoints[..self.num_watchpoints]
+    }
+
+    pub fn clear_watchpoints(&mut self) {
+        self.num_watchpoints = 0;
+    }
+
+    // called by instructions that store to RAM; remembers the first watched address hit
+    pub(crate) fn note_write(&mut self, addr: usize) {
+        if self.watch_hit.is_some() {
+            return;
+        }
+
+        let hit = self.watchpoints[..self.num_watchpoints]
+            .iter()
+            .any(|(start, end)| (*start as usize..*end as usize).contains(&addr));
+        if hit {
+            self.watch_hit = Some(addr as u16);
+        }
+    }
+
+    // pin VX to `val`: it's set now and put back after every instruction that changes it
+    // the instruction itself still sees whatever it wrote (e.g. a DRAW's VF) until it finishes
+    // returns false if `x` isn't a register; kept across `reset` like breakpoints
+    pub fn freeze_register(&mut self, x: usize, val: u8) -> bool {
+        if x >= NUM_REGS {
+            return false;
+        }
+
+        self.frozen_regs |= 1 << x;
+        self.frozen_values[x] = val;
+        self.v_reg[x] = val;
+        true
+    }
+
+    // let VX change again; returns false if it wasn't frozen
+    pub fn unfreeze_register(&mut self, x: usize) -> bool {
+        let was_frozen = x < NUM_REGS && self.frozen_regs & (1 << x) != 0;
+        if was_frozen {
+            self.frozen_regs &= !(1 << x);
+        }
+        was_frozen
+    }
+
+    // called after each instruction
+    pub(crate) fn hold_frozen_registers(&mut self) {
+        if self.frozen_regs == 0 {
+            return;
+        }
+        for x in 0..NUM_REGS {
+            if self.frozen_regs & (1 << x) != 0 {
+                self.v_reg[x] = self.frozen_values[x];
+            }
+        }
+    }
+
+    // call `hook` with every `DebugEvent` as it happens; kept across `reset`
+    #[cfg(feature = "alloc")]
+    pub fn set_event_hook(&mut self, hook: EventHook) {
+        self.event_hook = Some(hook);
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn clear_event_hook(&mut self) {
+        self.event_hook = None;
+    }
+
+    // report `DebugEvent::MisalignedPc` before each instruction fetched from an odd address
+    // execution carries on exactly the same either way; off by default, kept across `reset`
+    #[cfg(feature = "alloc")]
+    pub fn set_warn_on_misaligned_pc(&mut self, enabled: bool) {
+        self.warn_misaligned_pc = enabled;
+    }
+
+    // report `DebugEvent::StackPush` and `StackPop` for every CALL and RET, e.g. to draw a call tree
+    // or plot stack depth over time; off by default, kept across `reset`
+    #[cfg(feature = "alloc")]
+    pub fn set_trace_stack(&mut self, enabled: bool) {
+        self.trace_stack = enabled;
+    }
+
+    // hand an event to the hook, if there is one
+    #[cfg(feature = "alloc")]
+    pub(crate) fn emit(&mut self, event: DebugEvent) {
+        if let Some(hook) = self.event_hook.as_mut() {
+            hook(event);
+        }
+    }
+
+    // the opcode at pc, i.e. what the next `tick` will run
+    // like `fetch`, an opcode at 0xFFF takes its low byte from 0x000
+    pub fn peek_op(&self) -> u16 {
+        let pc = self.pc as usize;
+        ((self.ram[pc] as u16) << 8) | self.ram[(pc + 1) % RAM_SIZE] as u16
+    }
+
+    // run until pc lands on a breakpoint or a watched range is written, at most `max_cycles` instructions
+    // always runs at least one instruction so calling it again continues past the breakpoint
+    // like `run_cycles`, timers and input are left to the caller
+    pub fn run(&mut self, max_cycles: u64) -> Result<StopReason, ExecError> {
+        for _ in 0..max_cycles {
+            self.watch_hit = None;
+            self.tick()?;
+            if let Some(addr) = self.watch_hit.take() {
+                return Ok(StopReason::Watchpoint(addr));
+            }
+            if self.has_breakpoint(self.pc) {
+                return Ok(StopReason::Breakpoint(self.pc));
+            }
+        }
+
+        Ok(StopReason::CycleLimit)
+    }
+
+    // like a single `tick`, except a CALL (0x2NNN) runs the whole subroutine
+    // stops once the call returns to the next instruction, at any breakpoint inside it,
+    // or after a generous cycle limit if the subroutine never comes back
+    pub fn step_over(&mut self) -> Result<(), ExecError> {
+        if self.peek_op() & 0xF000 != 0x2000 {
+            return self.tick().map(|_| ());
+        }
+
+        // acts as a temporary breakpoint; the stack depth check handles recursive calls
+        let return_addr = self.pc.wrapping_add(2) % RAM_SIZE as u16;
+        let depth = self.sp;
+
+        self.tick()?;
+        for _ in 0..STEP_OVER_LIMIT {
+            if (self.pc == return_addr && self.sp == depth) || self.has_breakpoint(self.pc) {
+                break;
+            }
+            self.tick()?;
+        }
+
+        Ok(())
+    }
+}
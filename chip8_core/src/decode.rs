@@ -0,0 +1,220 @@
+use crate::Variant;
+
+// one instruction with its operands already pulled out of the opcode
+// `x` and `y` are register indices, `n`/`nn`/`nnn` are the 4, 8 and 12 bit immediates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decoded {
+    Nop,
+    Cls,
+    Ret,
+    ScrollDown { n: u8 },
+    ScrollRight,
+    ScrollLeft,
+    Lores,
+    Hires,
+    Jmp { nnn: u16 },
+    Call { nnn: u16 },
+    SkipEqNN { x: u8, nn: u8 },
+    SkipNeNN { x: u8, nn: u8 },
+    SkipEqVY { x: u8, y: u8 },
+    SetNN { x: u8, nn: u8 },
+    AddNN { x: u8, nn: u8 },
+    Set { x: u8, y: u8 },
+    Or { x: u8, y: u8 },
+    And { x: u8, y: u8 },
+    Xor { x: u8, y: u8 },
+    Add { x: u8, y: u8 },
+    Sub { x: u8, y: u8 },
+    Shr { x: u8, y: u8 },
+    SubN { x: u8, y: u8 },
+    Shl { x: u8, y: u8 },
+    SkipNeVY { x: u8, y: u8 },
+    SetI { nnn: u16 },
+    JmpV0 { nnn: u16 },
+    Rand { x: u8, nn: u8 },
+    Draw { x: u8, y: u8, n: u8 },
+    SkipKey { x: u8 },
+    SkipNotKey { x: u8 },
+    GetDelay { x: u8 },
+    WaitKey { x: u8 },
+    SetDelay { x: u8 },
+    SetSound { x: u8 },
+    AddI { x: u8 },
+    Font { x: u8 },
+    Bcd { x: u8 },
+    Store { x: u8 },
+    Load { x: u8 },
+    Plane { n: u8 },
+    SpriteHeight { x: u8 },
+    SaveFlags { x: u8 },
+    LoadFlags { x: u8 },
+    StoreRange { x: u8, y: u8 },
+    LoadRange { x: u8, y: u8 },
+}
+
+impl Decoded {
+    // index of the instruction's entry in `supported_opcodes()`
+    // DXY0 counts as the SUPER-CHIP 16x16 draw since that's the table's first match for it
+    pub fn id(&self) -> usize {
+        match self {
+            Decoded::Nop => 0,
+            Decoded::Cls => 1,
+            Decoded::Ret => 2,
+            Decoded::ScrollDown { .. } => 3,
+            Decoded::ScrollRight => 4,
+            Decoded::ScrollLeft => 5,
+            Decoded::Lores => 6,
+            Decoded::Hires => 7,
+            Decoded::Jmp { .. } => 8,
+            Decoded::Call { .. } => 9,
+            Decoded::SkipEqNN { .. } => 10,
+            Decoded::SkipNeNN { .. } => 11,
+            Decoded::SkipEqVY { .. } => 12,
+            Decoded::SetNN { .. } => 13,
+            Decoded::AddNN { .. } => 14,
+            Decoded::Set { .. } => 15,
+            Decoded::Or { .. } => 16,
+            Decoded::And { .. } => 17,
+            Decoded::Xor { .. } => 18,
+            Decoded::Add { .. } => 19,
+            Decoded::Sub { .. } => 20,
+            Decoded::Shr { .. } => 21,
+            Decoded::SubN { .. } => 22,
+            Decoded::Shl { .. } => 23,
+            Decoded::SkipNeVY { .. } => 24,
+            Decoded::SetI { .. } => 25,
+            Decoded::JmpV0 { .. } => 26,
+            Decoded::Rand { .. } => 27,
+            Decoded::Draw { n: 0, .. } => 28,
+            Decoded::Draw { .. } => 29,
+            Decoded::SkipKey { .. } => 30,
+            Decoded::SkipNotKey { .. } => 31,
+            Decoded::GetDelay { .. } => 32,
+            Decoded::WaitKey { .. } => 33,
+            Decoded::SetDelay { .. } => 34,
+            Decoded::SetSound { .. } => 35,
+            Decoded::AddI { .. } => 36,
+            Decoded::Font { .. } => 37,
+            Decoded::Bcd { .. } => 38,
+            Decoded::Store { .. } => 39,
+            Decoded::Load { .. } => 40,
+            Decoded::Plane { .. } => 41,
+            Decoded::SpriteHeight { .. } => 42,
+            Decoded::SaveFlags { .. } => 43,
+            Decoded::LoadFlags { .. } => 44,
+            Decoded::StoreRange { .. } => 45,
+            Decoded::LoadRange { .. } => 46,
+        }
+    }
+
+    // the first variant that supports the instruction
+    pub fn variant(&self) -> Variant {
+        match self {
+            Decoded::ScrollDown { .. }
+            | Decoded::ScrollRight
+            | Decoded::ScrollLeft
+            | Decoded::Lores
+            | Decoded::Hires
+            | Decoded::SaveFlags { .. }
+            | Decoded::LoadFlags { .. }
+            // DXY0 is the 16x16 sprite
+            | Decoded::Draw { n: 0, .. } => Variant::SuperChip,
+            Decoded::Plane { .. } | Decoded::StoreRange { .. } | Decoded::LoadRange { .. } => {
+                Variant::XoChip
+            }
+            _ => Variant::Chip8,
+        }
+    }
+
+    // whether running the instruction can change what's on screen
+    // LORES / HIRES count too since switching resolution clears the display
+    pub fn touches_display(&self) -> bool {
+        matches!(
+            self,
+            Decoded::Cls
+                | Decoded::Draw { .. }
+                | Decoded::ScrollDown { .. }
+                | Decoded::ScrollRight
+                | Decoded::ScrollLeft
+                | Decoded::Lores
+                | Decoded::Hires
+        )
+    }
+}
+
+// whether `op` can change what's on screen (see `Decoded::touches_display`), e.g. to skip presenting
+// frames where nothing could have been drawn; unknown opcodes never do
+pub fn opcode_touches_display(op: u16) -> bool {
+    decode(op).is_some_and(|instr| instr.touches_display())
+}
+
+// split an opcode into its instruction and operands
+// returns `None` for opcodes that aren't instructions in any supported variant
+pub fn decode(op: u16) -> Option<Decoded> {
+    let digit1 = (op & 0xF000) >> 12;
+    let digit2 = (op & 0x0F00) >> 8;
+    let digit3 = (op & 0x00F0) >> 4;
+    let digit4 = op & 0x000F;
+
+    // operands; not every instruction uses all of them
+    let x = digit2 as u8;
+    let y = digit3 as u8;
+    let n = digit4 as u8;
+    let nn = (op & 0xFF) as u8;
+    let nnn = op & 0xFFF;
+
+    let instr = match (digit1, digit2, digit3, digit4) {
+        (0, 0, 0, 0) => Decoded::Nop,
+        (0, 0, 0xE, 0) => Decoded::Cls,
+        (0, 0, 0xC, _) => Decoded::ScrollDown { n },
+        (0, 0, 0xF, 0xB) => Decoded::ScrollRight,
+        (0, 0, 0xF, 0xC) => Decoded::ScrollLeft,
+        (0, 0, 0xF, 0xE) => Decoded::Lores,
+        (0, 0, 0xF, 0xF) => Decoded::Hires,
+        (0, 0, 0xE, 0xE) => Decoded::Ret,
+        (1, _, _, _) => Decoded::Jmp { nnn },
+        (2, _, _, _) => Decoded::Call { nnn },
+        (3, _, _, _) => Decoded::SkipEqNN { x, nn },
+        (4, _, _, _) => Decoded::SkipNeNN { x, nn },
+        // least significant digit is not used in this operation; opcode requires it to be 0
+        (5, _, _, 0) => Decoded::SkipEqVY { x, y },
+        (5, _, _, 2) => Decoded::StoreRange { x, y },
+        (5, _, _, 3) => Decoded::LoadRange { x, y },
+        (6, _, _, _) => Decoded::SetNN { x, nn },
+        (7, _, _, _) => Decoded::AddNN { x, nn },
+        (8, _, _, 0) => Decoded::Set { x, y },
+        (8, _, _, 1) => Decoded::Or { x, y },
+        (8, _, _, 2) => Decoded::And { x, y },
+        (8, _, _, 3) => Decoded::Xor { x, y },
+        (8, _, _, 4) => Decoded::Add { x, y },
+        (8, _, _, 5) => Decoded::Sub { x, y },
+        (8, _, _, 6) => Decoded::Shr { x, y },
+        (8, _, _, 7) => Decoded::SubN { x, y },
+        (8, _, _, 0xE) => Decoded::Shl { x, y },
+        (9, _, _, 0) => Decoded::SkipNeVY { x, y },
+        (0xA, _, _, _) => Decoded::SetI { nnn },
+        (0xB, _, _, _) => Decoded::JmpV0 { nnn },
+        (0xC, _, _, _) => Decoded::Rand { x, nn },
+        (0xD, _, _, _) => Decoded::Draw { x, y, n },
+        (0xE, _, 9, 0xE) => Decoded::SkipKey { x },
+        (0xE, _, 0xA, 1) => Decoded::SkipNotKey { x },
+        (0xF, _, 0, 7) => Decoded::GetDelay { x },
+        (0xF, _, 0, 0xA) => Decoded::WaitKey { x },
+        (0xF, _, 1, 5) => Decoded::SetDelay { x },
+        (0xF, _, 1, 8) => Decoded::SetSound { x },
+        (0xF, _, 1, 0xE) => Decoded::AddI { x },
+        (0xF, _, 2, 9) => Decoded::Font { x },
+        (0xF, _, 3, 3) => Decoded::Bcd { x },
+        (0xF, _, 5, 5) => Decoded::Store { x },
+        (0xF, _, 6, 5) => Decoded::Load { x },
+        // the plane mask sits where X normally goes
+        (0xF, _, 0, 1) => Decoded::Plane { n: x },
+        // not part of any real variant; only runs with `Emu::set_tall_sprites`
+        (0xF, _, 1, 0xD) => Decoded::SpriteHeight { x },
+        (0xF, _, 7, 5) => Decoded::SaveFlags { x },
+        (0xF, _, 8, 5) => Decoded::LoadFlags { x },
+        (_, _, _, _) => return None,
+    };
+
+    Some(instr)
+}
@@ -0,0 +1,212 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::Emu;
+
+// at most this many `RamByte` and this many `Pixel` entries are reported, lowest address / first pixel first
+// one stray write is usually enough to go on and a cleared screen would otherwise be thousands of lines
+pub const MAX_DIFFS_PER_AREA: usize = 16;
+
+// one piece of machine state that differs between two emulators (`a` is `self`, `b` is `other`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateDiff {
+    Pc {
+        a: u16,
+        b: u16,
+    },
+    I {
+        a: u16,
+        b: u16,
+    },
+    Register {
+        index: u8,
+        a: u8,
+        b: u8,
+    },
+    Sp {
+        a: u16,
+        b: u16,
+    },
+    // only entries below both stack pointers are compared; the rest is stale
+    Stack {
+        index: u8,
+        a: u16,
+        b: u16,
+    },
+    DelayTimer {
+        a: u8,
+        b: u8,
+    },
+    SoundTimer {
+        a: u8,
+        b: u8,
+    },
+    Key {
+        index: u8,
+        a: bool,
+        b: bool,
+    },
+    RamByte {
+        addr: u16,
+        a: u8,
+        b: u8,
+    },
+    // pixels aren't compared when the resolutions differ
+    Hires {
+        a: bool,
+        b: bool,
+    },
+    Pixel {
+        plane: u8,
+        x: u8,
+        y: u8,
+        a: bool,
+        b: bool,
+    },
+}
+
+// `V3 differs: 0x05 vs 0x07`
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StateDiff::Pc { a, b } => write!(f, "PC differs: 0x{:03X} vs 0x{:03X}", a, b),
+            StateDiff::I { a, b } => write!(f, "I differs: 0x{:03X} vs 0x{:03X}", a, b),
+            StateDiff::Register { index, a, b } => {
+                write!(f, "V{:X} differs: 0x{:02X} vs 0x{:02X}", index, a, b)
+            }
+            StateDiff::Sp { a, b } => write!(f, "SP differs: {} vs {}", a, b),
+            StateDiff::Stack { index, a, b } => {
+                write!(f, "stack[{}] differs: 0x{:03X} vs 0x{:03X}", index, a, b)
+            }
+            StateDiff::DelayTimer { a, b } => write!(f, "DT differs: {} vs {}", a, b),
+            StateDiff::SoundTimer { a, b } => write!(f, "ST differs: {} vs {}", a, b),
+            StateDiff::Key { index, a, b } => {
+                write!(f, "key {:X} differs: {} vs {}", index, a, b)
+            }
+            StateDiff::RamByte { addr, a, b } => {
+                write!(f, "RAM 0x{:03X} differs: 0x{:02X} vs 0x{:02X}", addr, a, b)
+            }
+            StateDiff::Hires { a, b } => write!(f, "hires differs: {} vs {}", a, b),
+            StateDiff::Pixel { plane, x, y, a, b } => {
+                write!(
+                    f,
+                    "pixel ({}, {}) on plane {} differs: {} vs {}",
+                    x, y, plane, a, b
+                )
+            }
+        }
+    }
+}
+
+impl Emu {
+    // everything that differs between the two machines' state, CPU first, then RAM, then the screen
+    // configuration (variant, quirks, clock speed) isn't compared
+    pub fn diff(&self, other: &Emu) -> Vec<StateDiff> {
+        let mut diffs = Vec::new();
+
+        if self.pc != other.pc {
+            diffs.push(StateDiff::Pc {
+                a: self.pc,
+                b: other.pc,
+            });
+        }
+        if self.i_reg != other.i_reg {
+            diffs.push(StateDiff::I {
+                a: self.i_reg,
+                b: other.i_reg,
+            });
+        }
+        for (index, (a, b)) in self.v_reg.iter().zip(other.v_reg.iter()).enumerate() {
+            if a != b {
+                diffs.push(StateDiff::Register {
+                    index: index as u8,
+                    a: *a,
+                    b: *b,
+                });
+            }
+        }
+        if self.sp != other.sp {
+            diffs.push(StateDiff::Sp {
+                a: self.sp,
+                b: other.sp,
+            });
+        }
+        let depth = self.sp.min(other.sp) as usize;
+        for (index, (a, b)) in self.stack[..depth]
+            .iter()
+            .zip(other.stack.iter())
+            .enumerate()
+        {
+            if a != b {
+                diffs.push(StateDiff::Stack {
+                    index: index as u8,
+                    a: *a,
+                    b: *b,
+                });
+            }
+        }
+        if self.dt != other.dt {
+            diffs.push(StateDiff::DelayTimer {
+                a: self.dt,
+                b: other.dt,
+            });
+        }
+        if self.st != other.st {
+            diffs.push(StateDiff::SoundTimer {
+                a: self.st,
+                b: other.st,
+            });
+        }
+        for (index, (a, b)) in self.keys.iter().zip(other.keys.iter()).enumerate() {
+            if a != b {
+                diffs.push(StateDiff::Key {
+                    index: index as u8,
+                    a: *a,
+                    b: *b,
+                });
+            }
+        }
+
+        let ram = self.ram.iter().zip(other.ram.iter()).enumerate();
+        diffs.extend(
+            ram.filter(|(_, (a, b))| a != b)
+                .take(MAX_DIFFS_PER_AREA)
+                .map(|(addr, (a, b))| StateDiff::RamByte {
+                    addr: addr as u16,
+                    a: *a,
+                    b: *b,
+                }),
+        );
+
+        if self.hires != other.hires {
+            diffs.push(StateDiff::Hires {
+                a: self.hires,
+                b: other.hires,
+            });
+            return diffs;
+        }
+        let width = self.display_width();
+        let len = width * self.display_height();
+        let pixels = (0..self.plane_count.max(other.plane_count)).flat_map(|plane| {
+            let (a, b) = (&self.screen[plane][..len], &other.screen[plane][..len]);
+            a.iter()
+                .zip(b.iter())
+                .enumerate()
+                .map(move |(i, (a, b))| (plane, i, *a, *b))
+        });
+        diffs.extend(
+            pixels
+                .filter(|(_, _, a, b)| a != b)
+                .take(MAX_DIFFS_PER_AREA)
+                .map(|(plane, i, a, b)| StateDiff::Pixel {
+                    plane: plane as u8,
+                    x: (i % width) as u8,
+                    y: (i / width) as u8,
+                    a,
+                    b,
+                }),
+        );
+
+        diffs
+    }
+}
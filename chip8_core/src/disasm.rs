@@ -0,0 +1,93 @@
+use core::fmt;
+
+use crate::{decode, Decoded, START_ADDR};
+
+// one instruction (or data word) of a ROM listing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisasmLine {
+    // where the word is loaded in RAM
+    pub addr: u16,
+    pub op: u16,
+    // `None` for words that aren't an instruction in any variant (usually sprite data)
+    pub instr: Option<Decoded>,
+}
+
+// linear sweep over a ROM as it would be loaded at 0x200, one 2 byte word at a time
+// code and data are mixed in CHIP-8 ROMs so sprites show up as (often nonsense) instructions too
+// a trailing odd byte is padded with 0x00 and always listed as data
+pub fn disassemble_rom(rom: &[u8]) -> impl Iterator<Item = DisasmLine> + '_ {
+    rom.chunks(2).enumerate().map(|(i, word)| {
+        let op = u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or(0)]);
+        DisasmLine {
+            addr: START_ADDR.wrapping_add(2 * i as u16),
+            op,
+            instr: if word.len() == 2 { decode(op) } else { None },
+        }
+    })
+}
+
+// `0200  00E0  CLS`: address, the raw opcode, then the instruction
+impl fmt::Display for DisasmLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04X}  {:04X}  ", self.addr, self.op)?;
+        match self.instr {
+            Some(instr) => write!(f, "{}", instr),
+            None => write!(f, "DATA 0x{:04X}", self.op),
+        }
+    }
+}
+
+// the mnemonics from `supported_opcodes()` with the operands filled in
+// registers are written V0 - VF, immediates in hex
+impl fmt::Display for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Decoded::Nop => write!(f, "NOP"),
+            Decoded::Cls => write!(f, "CLS"),
+            Decoded::Ret => write!(f, "RET"),
+            Decoded::ScrollDown { n } => write!(f, "SCROLL DOWN {}", n),
+            Decoded::ScrollRight => write!(f, "SCROLL RIGHT"),
+            Decoded::ScrollLeft => write!(f, "SCROLL LEFT"),
+            Decoded::Lores => write!(f, "LORES"),
+            Decoded::Hires => write!(f, "HIRES"),
+            Decoded::Jmp { nnn } => write!(f, "JMP 0x{:03X}", nnn),
+            Decoded::Call { nnn } => write!(f, "CALL 0x{:03X}", nnn),
+            Decoded::SkipEqNN { x, nn } => write!(f, "SKIP V{:X} == 0x{:02X}", x, nn),
+            Decoded::SkipNeNN { x, nn } => write!(f, "SKIP V{:X} != 0x{:02X}", x, nn),
+            Decoded::SkipEqVY { x, y } => write!(f, "SKIP V{:X} == V{:X}", x, y),
+            Decoded::SetNN { x, nn } => write!(f, "V{:X} = 0x{:02X}", x, nn),
+            Decoded::AddNN { x, nn } => write!(f, "V{:X} += 0x{:02X}", x, nn),
+            Decoded::Set { x, y } => write!(f, "V{:X} = V{:X}", x, y),
+            Decoded::Or { x, y } => write!(f, "V{:X} |= V{:X}", x, y),
+            Decoded::And { x, y } => write!(f, "V{:X} &= V{:X}", x, y),
+            Decoded::Xor { x, y } => write!(f, "V{:X} ^= V{:X}", x, y),
+            Decoded::Add { x, y } => write!(f, "V{:X} += V{:X}", x, y),
+            Decoded::Sub { x, y } => write!(f, "V{:X} -= V{:X}", x, y),
+            Decoded::Shr { x, .. } => write!(f, "V{:X} >>= 1", x),
+            Decoded::SubN { x, y } => write!(f, "V{:X} = V{:X} - V{:X}", x, y, x),
+            Decoded::Shl { x, .. } => write!(f, "V{:X} <<= 1", x),
+            Decoded::SkipNeVY { x, y } => write!(f, "SKIP V{:X} != V{:X}", x, y),
+            Decoded::SetI { nnn } => write!(f, "I = 0x{:03X}", nnn),
+            Decoded::JmpV0 { nnn } => write!(f, "JMP V0 + 0x{:03X}", nnn),
+            Decoded::Rand { x, nn } => write!(f, "V{:X} = rand() & 0x{:02X}", x, nn),
+            Decoded::Draw { x, y, n } => write!(f, "DRAW V{:X} V{:X} {}", x, y, n),
+            Decoded::SkipKey { x } => write!(f, "SKIP KEY PRESS V{:X}", x),
+            Decoded::SkipNotKey { x } => write!(f, "SKIP KEY RELEASE V{:X}", x),
+            Decoded::GetDelay { x } => write!(f, "V{:X} = DT", x),
+            Decoded::WaitKey { x } => write!(f, "WAIT KEY V{:X}", x),
+            Decoded::SetDelay { x } => write!(f, "DT = V{:X}", x),
+            Decoded::SetSound { x } => write!(f, "ST = V{:X}", x),
+            Decoded::AddI { x } => write!(f, "I += V{:X}", x),
+            Decoded::Font { x } => write!(f, "I = FONT V{:X}", x),
+            Decoded::Bcd { x } => write!(f, "BCD V{:X}", x),
+            Decoded::Store { x } => write!(f, "STORE V0 - V{:X}", x),
+            Decoded::Load { x } => write!(f, "LOAD V0 - V{:X}", x),
+            Decoded::Plane { n } => write!(f, "PLANE {}", n),
+            Decoded::SpriteHeight { x } => write!(f, "SPRH V{:X}", x),
+            Decoded::SaveFlags { x } => write!(f, "SAVE FLAGS V0 - V{:X}", x),
+            Decoded::LoadFlags { x } => write!(f, "LOAD FLAGS V0 - V{:X}", x),
+            Decoded::StoreRange { x, y } => write!(f, "STORE V{:X} - V{:X}", x, y),
+            Decoded::LoadRange { x, y } => write!(f, "LOAD V{:X} - V{:X}", x, y),
+        }
+    }
+}
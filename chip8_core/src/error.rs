@@ -0,0 +1,204 @@
+use core::fmt;
+
+// errors raised while executing an instruction
+// `pc` is the address of the offending instruction (the emulator is left pointing at it)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecError {
+    // opcode doesn't match any known instruction
+    UnknownOpcode { pc: u16, op: u16 },
+    // CALL with a full stack
+    StackOverflow { pc: u16 },
+    // RET with an empty stack
+    StackUnderflow { pc: u16 },
+    // FX33 / FX55 / 5XY2 writing to `addr`, below 0x200, while `set_protect_low_ram` is on
+    ProtectedWrite { pc: u16, addr: u16 },
+    // FX55 / FX65 / 5XY2 / 5XY3 with I at `addr` would run past the end of RAM
+    RamOutOfBounds { pc: u16, addr: u16 },
+}
+
+impl ExecError {
+    // address of the offending instruction
+    pub fn pc(&self) -> u16 {
+        match *self {
+            ExecError::UnknownOpcode { pc, .. }
+            | ExecError::StackOverflow { pc }
+            | ExecError::StackUnderflow { pc }
+            | ExecError::ProtectedWrite { pc, .. }
+            | ExecError::RamOutOfBounds { pc, .. } => pc,
+        }
+    }
+}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecError::UnknownOpcode { pc, op } => {
+                write!(f, "unknown opcode 0x{:04X} at pc 0x{:04X}", op, pc)
+            }
+            ExecError::StackOverflow { pc } => write!(f, "stack overflow at pc 0x{:04X}", pc),
+            ExecError::StackUnderflow { pc } => write!(f, "stack underflow at pc 0x{:04X}", pc),
+            ExecError::ProtectedWrite { pc, addr } => write!(
+                f,
+                "write to protected address 0x{:03X} at pc 0x{:04X}",
+                addr, pc
+            ),
+            ExecError::RamOutOfBounds { pc, addr } => write!(
+                f,
+                "access from 0x{:03X} runs past the end of RAM at pc 0x{:04X}",
+                addr, pc
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExecError {}
+
+// an address or index outside the range an operation accepts (valid values are `0..len`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "0x{:X} is out of bounds (expected less than 0x{:X})",
+            self.index, self.len
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OutOfBounds {}
+
+// a caller-provided buffer that isn't the length an operation needs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeMismatch {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for SizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "buffer holds {} elements but {} are needed",
+            self.actual, self.expected
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SizeMismatch {}
+
+// errors raised while loading a ROM into memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    // ROM has no bytes at all, so there'd be nothing to run
+    Empty,
+    // ROM doesn't fit between the program start (0x200) and the end of RAM
+    TooLarge { len: usize, max: usize },
+    // Intel HEX record that can't be parsed or has a bad checksum (`line` counts from 1)
+    MalformedHex { line: usize },
+    // Intel HEX record that would write past the end of RAM
+    HexOutOfRange { line: usize, addr: usize },
+    // `load_hexdump` line that can't be parsed or names bytes past the end of RAM (`line` counts from 1)
+    MalformedDump { line: usize },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Empty => write!(f, "ROM is empty"),
+            LoadError::TooLarge { len, max } => write!(
+                f,
+                "ROM is {} bytes but at most {} bytes fit in memory",
+                len, max
+            ),
+            LoadError::MalformedHex { line } => {
+                write!(f, "malformed Intel HEX record on line {}", line)
+            }
+            LoadError::HexOutOfRange { line, addr } => write!(
+                f,
+                "Intel HEX record on line {} writes to 0x{:X}, past the end of memory",
+                line, addr
+            ),
+            LoadError::MalformedDump { line } => {
+                write!(f, "malformed hex dump on line {}", line)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LoadError {}
+
+// any error the core can raise, so frontends can handle them all in one place
+// stack overflow / underflow are execution errors and are reported through `Exec`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chip8Error {
+    Load(LoadError),
+    Exec(ExecError),
+    OutOfBounds(OutOfBounds),
+    SizeMismatch(SizeMismatch),
+}
+
+impl Chip8Error {
+    // address of the instruction that caused the error, if it happened while running
+    pub fn pc(&self) -> Option<u16> {
+        match self {
+            Chip8Error::Exec(err) => Some(err.pc()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Chip8Error::Load(err) => write!(f, "unable to load ROM: {}", err),
+            Chip8Error::Exec(err) => write!(f, "emulation stopped: {}", err),
+            Chip8Error::OutOfBounds(err) => err.fmt(f),
+            Chip8Error::SizeMismatch(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Chip8Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Chip8Error::Load(err) => Some(err),
+            Chip8Error::Exec(err) => Some(err),
+            Chip8Error::OutOfBounds(err) => Some(err),
+            Chip8Error::SizeMismatch(err) => Some(err),
+        }
+    }
+}
+
+impl From<LoadError> for Chip8Error {
+    fn from(err: LoadError) -> Self {
+        Chip8Error::Load(err)
+    }
+}
+
+impl From<ExecError> for Chip8Error {
+    fn from(err: ExecError) -> Self {
+        Chip8Error::Exec(err)
+    }
+}
+
+impl From<OutOfBounds> for Chip8Error {
+    fn from(err: OutOfBounds) -> Self {
+        Chip8Error::OutOfBounds(err)
+    }
+}
+
+impl From<SizeMismatch> for Chip8Error {
+    fn from(err: SizeMismatch) -> Self {
+        Chip8Error::SizeMismatch(err)
+    }
+}
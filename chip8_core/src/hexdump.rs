@@ -0,0 +1,90 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use core::fmt::Write;
+
+use crate::{Emu, LoadError, RAM_SIZE};
+
+// classic hex dump of RAM, for attaching machine state to bug reports
+//
+// one line per 16 bytes (the last line may be shorter), each ending in '\n':
+//   0200  00 E0 A2 2A 60 0C 61 08 D0 1F 70 09 A2 39 D0 1F  |...*`.a...p..9..|
+// - the address of the line's first byte as 4 uppercase hex digits
+// - two spaces, then each byte as 2 uppercase hex digits separated by single spaces,
+//   padded with spaces to the width of 16 bytes on a short last line
+// - two spaces, then the same bytes as ASCII between '|', with anything outside 0x20..=0x7E shown as '.'
+//
+// `load_hexdump` only reads the address and the bytes (everything before the first '|'),
+// so the ASCII column can be dropped or edited freely; blank lines are skipped
+const BYTES_PER_LINE: usize = 16;
+
+impl Emu {
+    // dump `len` bytes of RAM starting at `start`; stops early at the end of RAM
+    #[cfg(feature = "alloc")]
+    pub fn ram_hexdump(&self, start: u16, len: usize) -> String {
+        let start = (start as usize).min(RAM_SIZE);
+        let end = start.saturating_add(len).min(RAM_SIZE);
+        let mut text = String::new();
+
+        for (i, line) in self.ram[start..end].chunks(BYTES_PER_LINE).enumerate() {
+            // writing to a String can't fail
+            let _ = write!(text, "{:04X} ", start + i * BYTES_PER_LINE);
+            for byte in line {
+                let _ = write!(text, " {:02X}", byte);
+            }
+            for _ in line.len()..BYTES_PER_LINE {
+                text.push_str("   ");
+            }
+
+            text.push_str("  |");
+            for byte in line {
+                text.push(match byte {
+                    0x20..=0x7E => *byte as char,
+                    _ => '.',
+                });
+            }
+            text.push_str("|\n");
+        }
+
+        text
+    }
+
+    // write a dump made by `ram_hexdump` back into RAM at the addresses it names
+    // the whole dump is checked before anything is written, so RAM is untouched on error
+    pub fn load_hexdump(&mut self, text: &str) -> Result<(), LoadError> {
+        for_each_line(text, |_, _| {})?;
+        for_each_line(text, |addr, byte| self.ram[addr] = byte)
+    }
+}
+
+// parse every line, calling `write` with each byte's address
+fn for_each_line(text: &str, mut write: impl FnMut(usize, u8)) -> Result<(), LoadError> {
+    for (i, line) in text.lines().enumerate() {
+        let malformed = LoadError::MalformedDump { line: i + 1 };
+        // the ASCII column is only there for people
+        let line = line.split('|').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let addr = fields
+            .next()
+            .filter(|addr| addr.len() == 4)
+            .and_then(|addr| usize::from_str_radix(addr, 16).ok())
+            .ok_or(malformed)?;
+
+        for (offset, byte) in fields.enumerate() {
+            let byte = match byte.len() {
+                2 => u8::from_str_radix(byte, 16).map_err(|_| malformed)?,
+                _ => return Err(malformed),
+            };
+            if offset == BYTES_PER_LINE || addr + offset >= RAM_SIZE {
+                return Err(malformed);
+            }
+            write(addr + offset, byte);
+        }
+    }
+
+    Ok(())
+}
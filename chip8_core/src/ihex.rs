@@ -0,0 +1,104 @@
+use crate::{Emu, LoadError, RAM_SIZE};
+
+// Intel HEX record types (https://en.wikipedia.org/wiki/Intel_HEX)
+const DATA: u8 = 0x00;
+const END_OF_FILE: u8 = 0x01;
+const EXTENDED_SEGMENT: u8 = 0x02;
+const START_SEGMENT: u8 = 0x03;
+const EXTENDED_LINEAR: u8 = 0x04;
+const START_LINEAR: u8 = 0x05;
+
+// longest possible record: 255 data bytes plus length, address (2), type and checksum
+const MAX_RECORD: usize = 255 + 5;
+
+impl Emu {
+    // load a ROM in Intel HEX format, as emitted by some CHIP-8 assemblers
+    // data records are written to the RAM addresses they name (usually starting at 0x200)
+    // the whole file is checked before anything is written, so RAM is untouched on error
+    // a file without any data (e.g. just the end of file record) is `LoadError::Empty`, like an empty ROM
+    pub fn load_hex(&mut self, text: &str) -> Result<(), LoadError> {
+        let mut loaded = 0;
+        for_each_record(text, |_, data| loaded += data.len())?;
+        if loaded == 0 {
+            return Err(LoadError::Empty);
+        }
+        for_each_record(text, |addr, data| {
+            self.ram[addr..addr + data.len()].copy_from_slice(data);
+        })
+    }
+}
+
+// parse every record, calling `write` with each data record's address and bytes
+fn for_each_record(text: &str, mut write: impl FnMut(usize, &[u8])) -> Result<(), LoadError> {
+    // upper address bits set by extended address records
+    let mut base = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut record = [0; MAX_RECORD];
+        let record =
+            parse_record(line, &mut record).ok_or(LoadError::MalformedHex { line: line_no })?;
+        let (len, addr, kind, data) = (
+            record[0] as usize,
+            u16::from_be_bytes([record[1], record[2]]) as usize,
+            record[3],
+            &record[4..record.len() - 1],
+        );
+
+        match kind {
+            DATA => {
+                let start = base + addr;
+                if start + len > RAM_SIZE {
+                    return Err(LoadError::HexOutOfRange {
+                        line: line_no,
+                        addr: start + len - 1,
+                    });
+                }
+                write(start, data);
+            }
+            END_OF_FILE => return Ok(()),
+            EXTENDED_SEGMENT if len == 2 => {
+                base = (u16::from_be_bytes([data[0], data[1]]) as usize) << 4;
+            }
+            EXTENDED_LINEAR if len == 2 => {
+                base = (u16::from_be_bytes([data[0], data[1]]) as usize) << 16;
+            }
+            // start addresses are for x86 CPUs; CHIP-8 always starts at 0x200
+            START_SEGMENT | START_LINEAR => (),
+            _ => return Err(LoadError::MalformedHex { line: line_no }),
+        }
+    }
+
+    Ok(())
+}
+
+// decode one ":LLAAAATT..CC" line into `buf`, returning the bytes (length through checksum)
+// `None` if it isn't valid hex, the length doesn't match, or the checksum is wrong
+fn parse_record<'a>(line: &str, buf: &'a mut [u8; MAX_RECORD]) -> Option<&'a [u8]> {
+    let digits = line.strip_prefix(':')?.as_bytes();
+    if digits.len() % 2 != 0 || digits.len() / 2 < 5 || digits.len() / 2 > MAX_RECORD {
+        return None;
+    }
+
+    let bytes = &mut buf[..digits.len() / 2];
+    for (byte, pair) in bytes.iter_mut().zip(digits.chunks(2)) {
+        *byte = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+    }
+
+    // the length byte has to account for every data byte, and all bytes (checksum included) sum to 0
+    let sum = bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    if bytes[0] as usize + 5 != bytes.len() || sum != 0 {
+        return None;
+    }
+
+    Some(bytes)
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|digit| digit as u8)
+}
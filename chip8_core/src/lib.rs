@@ -1,12 +1,80 @@
+// no_std unless the `std` feature is on so the core can be embedded on microcontrollers
+// every buffer is a fixed-size array so no allocator is needed either
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "rand")]
 use rand::random;
 
+#[cfg(feature = "asm")]
+pub mod asm;
+mod builder;
+mod debug;
+mod decode;
+#[cfg(feature = "alloc")]
+mod diff;
+mod disasm;
+mod error;
+mod hexdump;
+#[cfg(feature = "ihex")]
+mod ihex;
+mod opcodes;
+mod quirks;
+#[cfg(feature = "replay")]
+mod replay;
+#[cfg(feature = "alloc")]
+mod rewind;
+#[cfg(feature = "alloc")]
+mod snapshot;
+#[cfg(feature = "test_roms")]
+pub mod test_roms;
+pub use builder::EmuBuilder;
+#[cfg(feature = "alloc")]
+pub use debug::EventHook;
+pub use debug::{DebugEvent, StopReason};
+use debug::{MAX_BREAKPOINTS, MAX_WATCHPOINTS};
+pub use decode::{decode, opcode_touches_display, Decoded};
+#[cfg(feature = "alloc")]
+pub use diff::{StateDiff, MAX_DIFFS_PER_AREA};
+pub use disasm::{disassemble_rom, DisasmLine};
+pub use error::{Chip8Error, ExecError, LoadError, OutOfBounds, SizeMismatch};
+pub use opcodes::{supported_opcodes, weighted_costs, OpcodeInfo, NUM_OPCODES};
+pub use quirks::Quirks;
+#[cfg(feature = "replay")]
+pub use replay::{replay_and_hash, GoldenRom, GOLDEN_ROMS};
+#[cfg(feature = "alloc")]
+pub use snapshot::EmuState;
+
 // exposed to the "frontend" for rendering purposes
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
+// SUPER-CHIP high resolution mode doubles both dimensions
+pub const HIRES_WIDTH: usize = 128;
+pub const HIRES_HEIGHT: usize = 64;
+// XO-CHIP has two display planes; each pixel's color is picked from the combination of planes it's lit in
+pub const NUM_PLANES: usize = 2;
+// some XO-CHIP forks go up to four planes (16 colors); see `Emu::set_plane_count`
+// FN01's plane mask is a single nibble so there's no room for more
+pub const MAX_PLANES: usize = 4;
+// deepest stack `EmuBuilder::stack_size` can configure
+pub const MAX_STACK_SIZE: usize = 64;
+// tallest sprite FX1D can ask for (see `Emu::set_tall_sprites`)
+pub const MAX_SPRITE_HEIGHT: u8 = 32;
+// how many of the last executed instructions `Emu::recent_opcodes` remembers
+pub const RECENT_OPS: usize = 16;
 
 // size in bytes
 const RAM_SIZE: usize = 4096;
 // implementing stack from scratch since wasm doesn't fully support std
+// 16 levels by default; `EmuBuilder::stack_size` allows up to `MAX_STACK_SIZE` for deeply recursive programs
 const STACK_SIZE: usize = 16;
 // 16 possible keys numbered 0x0 to 0xF
 const NUM_KEYS: usize = 16;
@@ -14,8 +82,11 @@ const NUM_KEYS: usize = 16;
 const START_ADDR: u16 = 0x200;
 // 16 V registers (from V0 to VF)
 const NUM_REGS: usize = 16;
-// sprites are 8 pixels wide and 5 pixels high
-const FONTSET_SIZE: usize = 80;
+// the hex digit sprites live at the very start of RAM, one after another
+pub const FONTSET_START: usize = 0;
+// sprites are 8 pixels wide and 5 pixels high, so each digit takes 5 bytes
+pub const FONT_CHAR_SIZE: usize = 5;
+const FONTSET_SIZE: usize = 16 * FONT_CHAR_SIZE;
 
 // number sprites
 const FONTSET: [u8; FONTSET_SIZE] = [
@@ -37,12 +108,220 @@ const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
 
+// which flavour of CHIP-8 the emulator runs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    // original COSMAC VIP instruction set
+    #[default]
+    Chip8,
+    // SUPER-CHIP 1.1: adds high resolution mode, 16x16 sprites, and scrolling
+    SuperChip,
+    // XO-CHIP (Octo): SUPER-CHIP plus a second display plane
+    // sprites wrap around the screen edges like CHIP-8 instead of being clipped
+    XoChip,
+}
+
+impl Variant {
+    // whether this variant runs instructions introduced by `other`
+    fn includes(self, other: Variant) -> bool {
+        match self {
+            Variant::XoChip => true,
+            _ => other == Variant::Chip8 || self == other,
+        }
+    }
+}
+
+// what happened during `Emu::run_frame`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameOutcome {
+    // the display differs from the previous frame (or was cleared / reset) and should be redrawn
+    pub screen_changed: bool,
+    // the sound timer is still running after this frame's timer tick (always false when silent)
+    pub beeping: bool,
+}
+
+// parameters of a DRAW (0xDXYN), for debuggers that want to highlight it (see `Emu::last_draw`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DrawInfo {
+    // screen position of the top left corner: VX and VY wrapped onto the screen
+    pub x: u8,
+    pub y: u8,
+    // sprite size in pixels (16x16 for SUPER-CHIP's DXY0)
+    pub width: u8,
+    pub height: u8,
+    // where the sprite data was read from (the I register)
+    pub addr: u16,
+}
+
+// what happened during a single `Emu::tick`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TickOutcome {
+    // the instruction was a DRAW that actually drew (not one held back by the `display_wait` quirk)
+    pub drew: bool,
+}
+
+// a V register, V0 to VF; out of range indices can't be made, so register access can't fail
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Reg(u8);
+
+impl Reg {
+    // the flag register, set by arithmetic carries and DRAW collisions
+    pub const VF: Reg = Reg(0xF);
+
+    // `None` unless `x` is 0x0 - 0xF
+    pub fn new(x: u8) -> Option<Reg> {
+        (x < NUM_REGS as u8).then_some(Reg(x))
+    }
+
+    // V0 through VF in order
+    pub fn all() -> impl Iterator<Item = Reg> {
+        (0..NUM_REGS as u8).map(Reg)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// how DRAW combines sprite pixels with the screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrawMode {
+    // classic CHIP-8: lit sprite pixels toggle the screen and VF reports collisions
+    #[default]
+    Xor,
+    // lit sprite pixels turn screen pixels on (OR); VF is always 0
+    Set,
+    // lit sprite pixels turn screen pixels off (AND NOT); VF is always 0
+    Clear,
+}
+
+// what `Emu::reset_with` starts over; the default is everything `reset` does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResetOptions {
+    // blank the display and go back to lores with one plane; off leaves the last frame showing
+    pub clear_screen: bool,
+    // zero RAM and put the fontset back; off leaves the ROM (and anything it wrote) in place
+    pub wipe_ram: bool,
+    // switch to the built-in generator starting from this seed (see `Emu::seed_rng`); `None` leaves
+    // the generator alone, like `reset`
+    pub reseed_rng: Option<u32>,
+}
+
+impl Default for ResetOptions {
+    fn default() -> Self {
+        Self {
+            clear_screen: true,
+            wipe_ram: true,
+            reseed_rng: None,
+        }
+    }
+}
+
+// a key press or release, as queued with `Emu::queue_key_event` or fed to a replay
+// applied right before instruction number `cycle` (counting from 0) runs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputEvent {
+    pub cycle: u64,
+    // 0x0 - 0xF; other values are ignored like they are by `Emu::keypress`
+    pub key: u8,
+    pub pressed: bool,
+}
+
+// which key FX0A reports when more than one is held
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyPriority {
+    // the lowest numbered key (0x0 before 0xF), like most interpreters
+    #[default]
+    LowestIndex,
+    // the key pressed last
+    MostRecent,
+}
+
+// where CXNN gets its random bytes from
+#[derive(Clone, Copy)]
+enum RngSource {
+    // built-in xorshift generator; works without std
+    Internal,
+    // caller-supplied generator (see `set_rng`)
+    Custom(fn() -> u8),
+    // thread-local generator from the `rand` crate
+    #[cfg(feature = "rand")]
+    Thread,
+}
+
+// how many key events `queue_key_event` can hold before the emulator catches up
+const KEY_QUEUE_LEN: usize = 32;
+
+// arbitrary non-zero starting state for the built-in generator (xorshift gets stuck at 0)
+const RNG_SEED: u32 = 0x2545_F491;
+
+// indices of the planes set in a FN01 style bitmask, in drawing order
+fn selected_planes(mask: u8) -> impl Iterator<Item = usize> {
+    (0..MAX_PLANES).filter(move |plane| mask & (1 << plane) != 0)
+}
+
+// registers VX to VY inclusive for 5XY2 / 5XY3, counting down when Y < X
+fn register_range(x: u8, y: u8) -> impl Iterator<Item = usize> {
+    let (x, y) = (x as usize, y as usize);
+    let (low, high) = (x.min(y), x.max(y));
+    (0..=high - low).map(move |offset| if x <= y { x + offset } else { x - offset })
+}
+
+// one bit per pixel of the (hires-sized) screen for flicker tracking
+const PIXEL_WORDS: usize = HIRES_WIDTH * HIRES_HEIGHT / 64;
+
+// FNV-1a constants for `state_hash` and `rom_checksum` (http://www.isthe.com/chongo/tech/comp/fnv/)
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+// a stable hash of a ROM's bytes, e.g. to key caches or save states by game rather than by file name
+// it only depends on the bytes (not the platform or the build) so it can be stored and compared later
+pub fn rom_checksum(data: &[u8]) -> u64 {
+    data.iter().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+// guess which variant a ROM was written for from the opcodes in it, e.g. to pick quirks automatically
+// any XO-CHIP only opcode (F000 NNNN long I, FN01 plane select, F002 audio, 5XY2 / 5XY3 register
+// ranges) means XO-CHIP; otherwise any SUPER-CHIP one (00CN / 00FB / 00FC scrolls, 00FE / 00FF
+// resolution, DXY0 big sprites, FX30 big font, FX75 / FX85 flags) means SUPER-CHIP
+// it's only a heuristic: words are read at even offsets like `disassemble_rom`, so sprites and other
+// data that happen to look like one of these opcodes (DXY0 is the usual culprit) give false positives
+pub fn detect_variant(data: &[u8]) -> Variant {
+    let mut variant = Variant::Chip8;
+    for word in data.chunks_exact(2) {
+        let op = u16::from_be_bytes([word[0], word[1]]);
+        let xo_chip = matches!(op & 0xF00F, 0x5002 | 0x5003)
+            || matches!(op, 0xF000 | 0xF002)
+            || op & 0xF0FF == 0xF001;
+        if xo_chip {
+            return Variant::XoChip;
+        }
+        let super_chip = op & 0xFFF0 == 0x00C0
+            || matches!(op, 0x00FB | 0x00FC | 0x00FE | 0x00FF)
+            || op & 0xF00F == 0xD000
+            || matches!(op & 0xF0FF, 0xF030 | 0xF075 | 0xF085);
+        if super_chip {
+            variant = Variant::SuperChip;
+        }
+    }
+    variant
+}
+
+// callback installed with `Emu::set_frame_hook`
+#[cfg(feature = "alloc")]
+pub type FrameHook = Box<dyn FnMut(&Emu)>;
+
 pub struct Emu {
     // program counter: keeps track of index of current instruction
     pc: u16,
 
     ram: [u8; RAM_SIZE],
-    screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    // one buffer per display plane, sized for hires; lores only uses the first SCREEN_WIDTH * SCREEN_HEIGHT entries
+    // only the first `plane_count` are ever drawn to
+    screen: [[bool; HIRES_WIDTH * HIRES_HEIGHT]; MAX_PLANES],
+    plane_count: usize,
     v_reg: [u8; NUM_REGS],
 
     // i register: used for indexing into RAM for reads and writes
@@ -50,80 +329,764 @@ pub struct Emu {
 
     // stack implementation
     sp: u16,
-    stack: [u16; STACK_SIZE],
+    stack: [u16; MAX_STACK_SIZE],
+    // how many entries of `stack` are usable (set with `EmuBuilder::stack_size`)
+    stack_size: usize,
 
     keys: [bool; NUM_KEYS],
+    // bitmasks of keys pressed since the last instruction ran, and of those already released again
+    // a release is held back until an instruction has seen the press (see `keypress`)
+    fresh_keys: u16,
+    late_releases: u16,
+    // FX0A tie-break (see `set_key_priority`), and the order keys were pressed in for `MostRecent`
+    key_priority: KeyPriority,
+    press_order: [u32; NUM_KEYS],
+    presses: u32,
+    // events from `queue_key_event` that no instruction has seen yet, oldest first
+    key_queue: [InputEvent; KEY_QUEUE_LEN],
+    queued_keys: usize,
 
     // delay timer (countdown) and sound timer (emits sound at 0)
     dt: u8,
     st: u8,
+
+    // number of instructions executed since the last reset
+    cycles: u64,
+
+    // cpu frequency for automatic timers (0 = the frontend calls `tick_timers` itself)
+    // `timer_acc` gains 60 per instruction; every `clock_hz` of it is one timer tick
+    clock_hz: u32,
+    timer_acc: u32,
+
+    // random number generation for CXNN
+    rng: RngSource,
+    rng_state: u32,
+    // values CXNN takes in turn before asking `rng` (see `set_rng_override`), and the next one to use
+    #[cfg(feature = "alloc")]
+    rng_override: Vec<u8>,
+    #[cfg(feature = "alloc")]
+    rng_override_pos: usize,
+
+    // instruction set in use and whether SUPER-CHIP high resolution mode is on
+    variant: Variant,
+    hires: bool,
+    // bitmask of the planes CLS, DRAW, and the scrolls operate on (XO-CHIP FN01)
+    planes: u8,
+    draw_mode: DrawMode,
+    quirks: Quirks,
+    // a DRAW already ran this frame (for the `display_wait` quirk)
+    frame_drawn: bool,
+    // the most recent DRAW (see `last_draw`)
+    last_draw: Option<DrawInfo>,
+    // the most recently executed opcode (see `last_opcode`)
+    last_op: Option<u16>,
+    // (address, opcode) of the last `RECENT_OPS` instructions, used as a ring buffer
+    // `num_recent` counts every instruction since the last reset so it also says where the ring starts
+    recent: [(u16, u16); RECENT_OPS],
+    num_recent: u64,
+    // set when an instruction touches the screen; cleared by `run_frame`
+    display_changed: bool,
+    // the current instruction drew a sprite (reported through `TickOutcome`)
+    tick_drew: bool,
+    // never ask the host to beep (see `set_silent`)
+    silent: bool,
+    // FX1D is allowed (see `set_tall_sprites`), and the height it set for the next DRAW
+    tall_sprites: bool,
+    sprite_height: Option<u8>,
+    // FX33 / FX55 / 5XY2 writes below 0x200 are an error (see `set_protect_low_ram`)
+    protect_low_ram: bool,
+    // SUPER-CHIP's RPL user flags (FX75 / FX85); they outlive `reset` like the HP48's did
+    rpl_flags: [u8; NUM_REGS],
+
+    // per-instruction execution counts indexed by `Decoded::id` (only when enabled via the builder)
+    histogram: Option<[u64; NUM_OPCODES]>,
+    // cycles each instruction takes out of `run_frame`'s budget, indexed by `Decoded::id`
+    // `None` is the default uniform cost of 1 (only set via the builder)
+    costs: Option<[u8; NUM_OPCODES]>,
+    // the cost of the instruction that just ran
+    tick_cost: u8,
+
+    // debugger breakpoints; only the first `num_breakpoints` entries are in use
+    breakpoints: [u16; MAX_BREAKPOINTS],
+    num_breakpoints: usize,
+    // watched RAM ranges (`start..end`) and the first watched address written by the current instruction
+    watchpoints: [(u16, u16); MAX_WATCHPOINTS],
+    num_watchpoints: usize,
+    watch_hit: Option<u16>,
+    // registers pinned with `freeze_register` (one bit per register) and the values they're pinned to
+    frozen_regs: u16,
+    frozen_values: [u8; NUM_REGS],
+
+    // flicker diagnostics: pixels DRAW has turned off this frame, how many were turned back on,
+    // and the total from the last finished frame (see `flicker_score`)
+    erased: [u64; PIXEL_WORDS],
+    flicker: u32,
+    last_flicker: u32,
+
+    // per-instruction undo history (see `set_micro_rewind`)
+    #[cfg(feature = "alloc")]
+    undo: Option<rewind::UndoLog>,
+
+    // receives `DebugEvent`s (see `set_event_hook`)
+    #[cfg(feature = "alloc")]
+    event_hook: Option<EventHook>,
+    // report `DebugEvent::MisalignedPc` (see `set_warn_on_misaligned_pc`)
+    #[cfg(feature = "alloc")]
+    warn_misaligned_pc: bool,
+    // report `DebugEvent::StackPush` / `StackPop` (see `set_trace_stack`)
+    #[cfg(feature = "alloc")]
+    trace_stack: bool,
+
+    // called at the end of every `tick_timers` (see `set_frame_hook`)
+    #[cfg(feature = "alloc")]
+    frame_hook: Option<FrameHook>,
+}
+
+impl Default for Emu {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Emu {
-    // constructor
+    // constructor; see `EmuBuilder` for non-default configurations
     pub fn new() -> Self {
         let mut new_emu = Self {
             pc: START_ADDR,
             ram: [0; RAM_SIZE],
             // screen is a 1D array of boolean values (represents flipped / unflipped pixels)
-            screen: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+            screen: [[false; HIRES_WIDTH * HIRES_HEIGHT]; MAX_PLANES],
+            plane_count: NUM_PLANES,
             v_reg: [0; NUM_REGS],
             i_reg: 0,
             sp: 0,
-            stack: [0; STACK_SIZE],
+            stack: [0; MAX_STACK_SIZE],
+            stack_size: STACK_SIZE,
             keys: [false; NUM_KEYS],
+            fresh_keys: 0,
+            late_releases: 0,
+            key_priority: KeyPriority::LowestIndex,
+            press_order: [0; NUM_KEYS],
+            presses: 0,
+            key_queue: [InputEvent::default(); KEY_QUEUE_LEN],
+            queued_keys: 0,
             dt: 0,
             st: 0,
+            cycles: 0,
+            clock_hz: 0,
+            timer_acc: 0,
+            #[cfg(feature = "rand")]
+            rng: RngSource::Thread,
+            #[cfg(not(feature = "rand"))]
+            rng: RngSource::Internal,
+            rng_state: RNG_SEED,
+            #[cfg(feature = "alloc")]
+            rng_override: Vec::new(),
+            #[cfg(feature = "alloc")]
+            rng_override_pos: 0,
+            variant: Variant::Chip8,
+            hires: false,
+            planes: 1,
+            draw_mode: DrawMode::Xor,
+            quirks: Quirks::modern(),
+            frame_drawn: false,
+            last_draw: None,
+            last_op: None,
+            recent: [(0, 0); RECENT_OPS],
+            num_recent: 0,
+            display_changed: false,
+            histogram: None,
+            costs: None,
+            tick_cost: 1,
+            silent: false,
+            tick_drew: false,
+            tall_sprites: false,
+            sprite_height: None,
+            protect_low_ram: false,
+            rpl_flags: [0; NUM_REGS],
+            breakpoints: [0; MAX_BREAKPOINTS],
+            num_breakpoints: 0,
+            frozen_regs: 0,
+            frozen_values: [0; NUM_REGS],
+            watchpoints: [(0, 0); MAX_WATCHPOINTS],
+            num_watchpoints: 0,
+            watch_hit: None,
+            erased: [0; PIXEL_WORDS],
+            flicker: 0,
+            last_flicker: 0,
+            #[cfg(feature = "alloc")]
+            undo: None,
+            #[cfg(feature = "alloc")]
+            event_hook: None,
+            #[cfg(feature = "alloc")]
+            warn_misaligned_pc: false,
+            #[cfg(feature = "alloc")]
+            trace_stack: false,
+            #[cfg(feature = "alloc")]
+            frame_hook: None,
         };
 
         // copies all font sprites into RAM
-        new_emu.ram[..FONTSET_SIZE].copy_from_slice(&FONTSET);
+        new_emu.ram[FONTSET_START..FONTSET_START + FONTSET_SIZE].copy_from_slice(&FONTSET);
 
         new_emu
     }
 
+    // emulator that ticks its own timers at 60 Hz, assuming `tick` is called `hz` times per second
+    // `hz` = 0 is the same as `new` (timers only move on `tick_timers`)
+    pub fn new_with_clock_hz(hz: u32) -> Self {
+        let mut emu = Self::new();
+        emu.set_clock_hz(hz);
+        emu
+    }
+
+    // change the cpu frequency used for automatic timers; 0 turns them off
+    // kept across `reset`
+    pub fn set_clock_hz(&mut self, hz: u32) {
+        self.clock_hz = hz;
+        self.timer_acc = 0;
+    }
+
+    pub fn clock_hz(&self) -> u32 {
+        self.clock_hz
+    }
+
+    // switch instruction sets; opcodes from other variants are reported as unknown
+    // also switches to the variant's quirks preset (`Quirks::for_variant`); call `set_quirks` afterwards to override
+    // kept across `reset` since it's part of the machine's configuration
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+        self.quirks = Quirks::for_variant(variant);
+        self.hires = false;
+        self.planes = 1;
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    // choose interpreter quirks independently of the variant; kept across `reset`
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    // change how DRAW puts sprites on the screen; kept across `reset` like the variant
+    pub fn set_draw_mode(&mut self, mode: DrawMode) {
+        self.draw_mode = mode;
+    }
+
+    pub fn draw_mode(&self) -> DrawMode {
+        self.draw_mode
+    }
+
+    // choose which key FX0A reports when several are held (`LowestIndex` by default); kept across `reset`
+    pub fn set_key_priority(&mut self, priority: KeyPriority) {
+        self.key_priority = priority;
+    }
+
+    pub fn key_priority(&self) -> KeyPriority {
+        self.key_priority
+    }
+
+    // headless / deterministic runs: `run_frame` never reports a beep so the host never starts audio
+    // the sound timer itself still counts down exactly the same, so ROMs that read it back see no difference
+    // kept across `reset`
+    pub fn set_silent(&mut self, silent: bool) {
+        self.silent = silent;
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    // lint for ROM authors: FX33, FX55 and 5XY2 fail with `ExecError::ProtectedWrite` instead of writing
+    // below 0x200, where the interpreter and fontset live
+    // real interpreters let games write there, so it's off by default; kept across `reset`
+    pub fn set_protect_low_ram(&mut self, enabled: bool) {
+        self.protect_low_ram = enabled;
+    }
+
+    pub fn protect_low_ram(&self) -> bool {
+        self.protect_low_ram
+    }
+
+    // the RPL user flags written by FX75, which games use to keep high scores between runs
+    // frontends can save them when they change and hand them back with `set_rpl_flags` next time
+    pub fn rpl_flags(&self) -> [u8; NUM_REGS] {
+        self.rpl_flags
+    }
+
+    // restore flags saved from an earlier run, e.g. right after loading the ROM
+    pub fn set_rpl_flags(&mut self, flags: [u8; NUM_REGS]) {
+        self.rpl_flags = flags;
+    }
+
+    // experimental extension: FX1D makes the next DXYN draw a sprite VX rows tall instead of N
+    // (up to `MAX_SPRITE_HEIGHT`), for ROMs written for forks with taller sprites
+    // off by default, in which case FX1D is an unknown opcode like in every real variant; kept across `reset`
+    pub fn set_tall_sprites(&mut self, enabled: bool) {
+        self.tall_sprites = enabled;
+        self.sprite_height = None;
+    }
+
+    pub fn tall_sprites(&self) -> bool {
+        self.tall_sprites
+    }
+
+    // advanced: how many display planes FN01 can select, for XO-CHIP forks with up to `MAX_PLANES`
+    // the standard is `NUM_PLANES` (2, the default); clamped to 1..=`MAX_PLANES`
+    // with 4 planes `plane_bits` ranges over 16 colors instead of 4. planes past the new count are cleared
+    // kept across `reset`
+    pub fn set_plane_count(&mut self, count: usize) {
+        self.plane_count = count.clamp(1, MAX_PLANES);
+        for plane in self.plane_count..MAX_PLANES {
+            self.screen[plane] = [false; HIRES_WIDTH * HIRES_HEIGHT];
+        }
+        self.planes &= self.plane_mask();
+        self.display_changed = true;
+    }
+
+    pub fn plane_count(&self) -> usize {
+        self.plane_count
+    }
+
+    // FN01 bits for every plane that exists
+    fn plane_mask(&self) -> u8 {
+        (1 << self.plane_count) - 1
+    }
+
+    // replace the random number generator used by CXNN
+    // takes a plain function so no allocator is needed; closures that don't capture anything coerce to it
+    pub fn set_rng(&mut self, rng: fn() -> u8) {
+        self.rng = RngSource::Custom(rng);
+    }
+
+    // make CXNN use exactly `values`, in order, as its random bytes (before the NN mask is applied)
+    // for tests that need a particular outcome; once the last value is used it starts over from the first
+    // an empty list removes the override and goes back to the configured generator
+    // kept across `reset`, but each call starts from the beginning of the list
+    #[cfg(feature = "alloc")]
+    pub fn set_rng_override(&mut self, values: Vec<u8>) {
+        self.rng_override = values;
+        self.rng_override_pos = 0;
+    }
+
+    // switch to the built-in generator starting from `seed`; the same seed gives the same CXNN results
+    pub fn seed_rng(&mut self, seed: u32) {
+        self.rng = RngSource::Internal;
+        // xorshift can't recover from an all-zero state
+        self.rng_state = if seed == 0 { RNG_SEED } else { seed };
+    }
+
+    // fetch the next random byte from the configured source
+    fn random_byte(&mut self) -> u8 {
+        #[cfg(feature = "alloc")]
+        if let Some(value) = self.rng_override.get(self.rng_override_pos) {
+            self.rng_override_pos = (self.rng_override_pos + 1) % self.rng_override.len();
+            return *value;
+        }
+        self.source_byte()
+    }
+
+    // the next byte from the configured generator, ignoring `set_rng_override`
+    fn source_byte(&mut self) -> u8 {
+        match self.rng {
+            RngSource::Internal => self.xorshift_byte(),
+            RngSource::Custom(rng) => rng(),
+            #[cfg(feature = "rand")]
+            RngSource::Thread => random(),
+        }
+    }
+
+    // step the built-in generator
+    fn xorshift_byte(&mut self) -> u8 {
+        // xorshift32 (https://en.wikipedia.org/wiki/Xorshift)
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        (x >> 24) as u8
+    }
+
+    // fill everything but the fontset with garbage, like RAM on real hardware at power on
+    // the bytes come from the CXNN generator, so a seeded machine gets the same garbage every time
+    // and an unseeded one with `rand` gets different garbage each run
+    fn randomize_ram(&mut self) {
+        for addr in FONTSET_START + FONTSET_SIZE..RAM_SIZE {
+            self.ram[addr] = self.source_byte();
+        }
+    }
+
+    // with `protect_low_ram`, refuse a write to `len` bytes from `start` (wrapping at the end of RAM)
+    // that touches the interpreter / font area
+    fn check_write(&self, start: usize, len: usize) -> Result<(), ExecError> {
+        if !self.protect_low_ram {
+            return Ok(());
+        }
+        match (start..start + len)
+            .map(|addr| addr % RAM_SIZE)
+            .find(|addr| *addr < START_ADDR as usize)
+        {
+            Some(addr) => Err(ExecError::ProtectedWrite {
+                pc: self.op_addr(),
+                addr: addr as u16,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // FX55 / FX65 / 5XY2 / 5XY3 touch `len` bytes from `start` and don't wrap, so running off the end is an error
+    fn check_ram(&self, start: usize, len: usize) -> Result<(), ExecError> {
+        if start + len > RAM_SIZE {
+            return Err(ExecError::RamOutOfBounds {
+                pc: self.op_addr(),
+                addr: start as u16,
+            });
+        }
+        Ok(())
+    }
+
     // stack push operation
-    fn push(&mut self, val: u16) {
+    fn push(&mut self, val: u16) -> Result<(), ExecError> {
+        // push on a full stack is a bug in the game; report it rather than panicking
+        if self.sp as usize >= self.stack_size {
+            return Err(ExecError::StackOverflow { pc: self.op_addr() });
+        }
+
         self.stack[self.sp as usize] = val;
         self.sp += 1;
+        #[cfg(feature = "alloc")]
+        if self.trace_stack {
+            self.emit(DebugEvent::StackPush {
+                pc: self.op_addr(),
+                addr: val,
+                sp: self.sp,
+            });
+        }
+        Ok(())
     }
 
     // stack pop operation
-    fn pop(&mut self) -> u16 {
-        // pop at 0 will cause underflow (i.e. rust panic)
-        // this situation will only be caused if there is a bug in the emulator / game so is left unhandled
+    fn pop(&mut self) -> Result<u16, ExecError> {
+        // pop at 0 would underflow; same as above, only caused by a bug in the game
+        if self.sp == 0 {
+            return Err(ExecError::StackUnderflow { pc: self.op_addr() });
+        }
+
         self.sp -= 1;
-        self.stack[self.sp as usize]
+        let val = self.stack[self.sp as usize];
+        #[cfg(feature = "alloc")]
+        if self.trace_stack {
+            self.emit(DebugEvent::StackPop {
+                pc: self.op_addr(),
+                addr: val,
+                sp: self.sp,
+            });
+        }
+        Ok(val)
+    }
+
+    // address of the instruction currently being executed
+    // `fetch` has already moved pc past it by the time `execute` runs
+    fn op_addr(&self) -> u16 {
+        self.pc.wrapping_sub(2) % RAM_SIZE as u16
+    }
+
+    // move pc past one opcode, wrapping back to 0x000 at the end of RAM instead of running off it
+    fn advance_pc(&mut self) {
+        self.pc = self.pc.wrapping_add(2) % RAM_SIZE as u16;
     }
 
     // reset back to initial state
     pub fn reset(&mut self) {
+        self.reset_with(ResetOptions::default());
+    }
+
+    // like `reset`, but the screen, RAM and RNG only start over if `opts` says so
+    // e.g. keep the last frame up while restarting, or restart the loaded ROM without reloading it
+    pub fn reset_with(&mut self, opts: ResetOptions) {
         self.pc = START_ADDR;
-        self.ram = [0; RAM_SIZE];
-        self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+        if opts.wipe_ram {
+            self.ram = [0; RAM_SIZE];
+            self.ram[FONTSET_START..FONTSET_START + FONTSET_SIZE].copy_from_slice(&FONTSET);
+        }
+        if opts.clear_screen {
+            self.screen = [[false; HIRES_WIDTH * HIRES_HEIGHT]; MAX_PLANES];
+            self.hires = false;
+            self.planes = 1;
+            // the old picture is gone so frontends need to redraw
+            self.display_changed = true;
+        }
+        if let Some(seed) = opts.reseed_rng {
+            self.seed_rng(seed);
+        }
+        self.frame_drawn = false;
+        self.last_draw = None;
+        self.last_op = None;
+        self.num_recent = 0;
+        self.sprite_height = None;
         self.v_reg = [0; NUM_REGS];
         self.i_reg = 0;
         self.sp = 0;
-        self.stack = [0; STACK_SIZE];
+        self.stack = [0; MAX_STACK_SIZE];
         self.keys = [false; NUM_KEYS];
+        self.fresh_keys = 0;
+        self.late_releases = 0;
+        self.press_order = [0; NUM_KEYS];
+        self.presses = 0;
+        self.queued_keys = 0;
         self.dt = 0;
         self.st = 0;
-        self.ram[..FONTSET_SIZE].copy_from_slice(&FONTSET);
+        self.cycles = 0;
+        self.timer_acc = 0;
+        self.erased = [0; PIXEL_WORDS];
+        self.flicker = 0;
+        self.last_flicker = 0;
+        self.reset_histogram();
+        #[cfg(feature = "alloc")]
+        if self.undo.is_some() {
+            self.undo = Some(rewind::UndoLog::new());
+        }
     }
 
     // cpu tick operation
-    pub fn tick(&mut self) {
+    pub fn tick(&mut self) -> Result<TickOutcome, ExecError> {
+        #[cfg(feature = "alloc")]
+        self.begin_undo();
+
+        self.apply_queued_key();
+
         // fetch
+        #[cfg(feature = "alloc")]
+        if self.warn_misaligned_pc && self.pc & 1 != 0 {
+            self.emit(DebugEvent::MisalignedPc { pc: self.pc });
+        }
+        self.tick_drew = false;
         let op = self.fetch();
         // decode & execute
-        self.execute(op);
+        let result = self.execute(op);
+        #[cfg(feature = "alloc")]
+        self.finish_undo(result.is_ok());
+        if let Err(err) = result {
+            // errors are raised before any state is touched
+            // rewind so pc points at the offending instruction for inspection
+            self.pc = self.op_addr();
+            return Err(err);
+        }
+
+        self.cycles += 1;
+        self.latch_keys();
+        self.hold_frozen_registers();
+
+        // automatic timers: exactly 60 timer ticks per `clock_hz` instructions, with no drift
+        if self.clock_hz > 0 {
+            self.timer_acc += 60;
+            while self.timer_acc >= self.clock_hz {
+                self.timer_acc -= self.clock_hz;
+                self.tick_timers();
+            }
+        }
+
+        Ok(TickOutcome {
+            drew: self.tick_drew,
+        })
+    }
+
+    // run up to `n` cycles, stopping early on the first error
+    // how far it got can be read from the error's pc and from `cycle_count`
+    // only the cpu is stepped: timers and input are not advanced unless the caller also drives them
+    pub fn run_cycles(&mut self, n: u64) -> Result<(), ExecError> {
+        for _ in 0..n {
+            self.tick()?;
+        }
+
+        Ok(())
+    }
+
+    // run until an instruction draws a sprite or clears the screen, or until `max_cycles` have run
+    // returns whether it stopped because of a draw (the drawing instruction has already run), e.g. for
+    // capture tools that want exactly one rendered frame however many instructions that takes
+    // like `run_cycles`, only the cpu is stepped
+    pub fn run_until_draw(&mut self, max_cycles: u64) -> Result<bool, ExecError> {
+        for _ in 0..max_cycles {
+            let clears = self.peek_op() == 0x00E0;
+            if self.tick()?.drew || clears {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    // run one 60 Hz frame: `ticks_per_frame` instructions followed by a single timer tick
+    // (fewer instructions if the `display_wait` quirk is on and one of them draws)
+    // with a cost table from `EmuBuilder::instruction_costs`, `ticks_per_frame` is a budget of cycles
+    // instead and each instruction spends its cost, so frames with slow instructions run fewer of them
+    // this is the loop every frontend needs, so they only have to render based on the outcome
+    // on error the frame stops early and the timers aren't ticked
+    pub fn run_frame(&mut self, ticks_per_frame: usize) -> Result<FrameOutcome, ExecError> {
+        let mut budget = ticks_per_frame;
+        while budget > 0 {
+            // like the COSMAC VIP, `display_wait` gives the frontend control back once a sprite is drawn
+            // so each 60 Hz frame shows at most one DRAW
+            if self.tick()?.drew && self.quirks.display_wait {
+                break;
+            }
+            budget = budget.saturating_sub(self.tick_cost as usize);
+        }
+
+        // timers tick once per frame, unless `clock_hz` already has them running off the instruction count
+        if self.clock_hz == 0 {
+            self.tick_timers();
+        }
+
+        let screen_changed = self.display_changed;
+        self.display_changed = false;
+
+        Ok(FrameOutcome {
+            screen_changed,
+            beeping: !self.silent && self.st > 0,
+        })
+    }
+
+    // how many times each instruction has executed, indexed like `supported_opcodes()`
+    // `None` unless enabled with `EmuBuilder::histogram`
+    pub fn opcode_histogram(&self) -> Option<&[u64]> {
+        self.histogram.as_ref().map(|counts| &counts[..])
+    }
+
+    // zero the execution counts (also done by `reset`)
+    pub fn reset_histogram(&mut self) {
+        if let Some(counts) = self.histogram.as_mut() {
+            *counts = [0; NUM_OPCODES];
+        }
+    }
+
+    // the `pattern` of every instruction that has executed at least once, in `supported_opcodes()` order
+    // comes from the histogram, so it's empty unless that's enabled and is cleared along with it
+    #[cfg(feature = "alloc")]
+    pub fn coverage(&self) -> Vec<u16> {
+        match self.histogram.as_ref() {
+            Some(counts) => supported_opcodes()
+                .iter()
+                .zip(counts.iter())
+                .filter(|(_, count)| **count > 0)
+                .map(|(info, _)| info.pattern)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // address of the next instruction to run
+    pub fn get_pc(&self) -> u16 {
+        self.pc
+    }
+
+    // move execution to `addr` (e.g. a debugger's "set next statement")
+    // this bypasses normal control flow (the stack is untouched) so it's meant for tooling, not games
+    // `addr` must leave room to fetch a whole 2 byte opcode from RAM
+    pub fn set_pc(&mut self, addr: u16) -> Result<(), OutOfBounds> {
+        if addr as usize >= RAM_SIZE - 1 {
+            return Err(OutOfBounds {
+                index: addr as usize,
+                len: RAM_SIZE - 1,
+            });
+        }
+
+        self.pc = addr;
+        Ok(())
+    }
+
+    // step over the instruction at pc without running it, e.g. to carry on after `tick` failed with
+    // `ExecError::UnknownOpcode` (which leaves pc on the offending opcode)
+    // like `set_pc` this is for debuggers: whatever the instruction would have done is simply lost
+    // wraps back to 0x000 past the end of RAM rather than leaving pc somewhere fetch can't read
+    pub fn skip_current(&mut self) {
+        self.advance_pc();
+    }
+
+    // value of register VX
+    pub fn get_register(&self, reg: Reg) -> u8 {
+        self.v_reg[reg.index()]
+    }
+
+    // overwrite VX, e.g. so a test harness can set up preconditions before running a snippet
+    pub fn set_register(&mut self, reg: Reg, val: u8) {
+        self.v_reg[reg.index()] = val;
+    }
+
+    // value of the I register
+    pub fn get_i(&self) -> u16 {
+        self.i_reg
+    }
+
+    // overwrite the I register
+    // not range checked, the same as ANNN / FX1E which can also leave it anywhere in 0x0000-0xFFFF
+    pub fn set_i(&mut self, val: u16) {
+        self.i_reg = val;
+    }
+
+    // how many nested CALLs fit before a stack overflow
+    pub fn stack_size(&self) -> usize {
+        self.stack_size
+    }
+
+    // number of instructions executed since the last reset
+    pub fn cycle_count(&self) -> u64 {
+        self.cycles
+    }
+
+    // time the emulated machine has been running since the last reset, at `clock_hz` instructions per second
+    // 0.0 when no clock speed is set since the cycle count alone doesn't say how long that took
+    pub fn emulated_seconds(&self) -> f64 {
+        if self.clock_hz == 0 {
+            return 0.0;
+        }
+        self.cycles as f64 / self.clock_hz as f64
+    }
+
+    // `len` bytes of RAM starting at `addr`, e.g. for a debugger's memory view
+    pub fn read_memory(&self, addr: u16, len: usize) -> Result<&[u8], OutOfBounds> {
+        let start = addr as usize;
+        // a huge `len` can't overflow into a range that looks valid
+        match start
+            .checked_add(len)
+            .and_then(|end| self.ram.get(start..end))
+        {
+            Some(bytes) => Ok(bytes),
+            None => Err(OutOfBounds {
+                index: start.saturating_add(len),
+                len: RAM_SIZE,
+            }),
+        }
+    }
+
+    // overwrite the instruction at `addr` with `op` (high byte first, the way `fetch` reads it back),
+    // e.g. for a debugger patching a game while it runs
+    // like `set_pc`, `addr` must leave room for both bytes
+    pub fn poke_opcode(&mut self, addr: u16, op: u16) -> Result<(), OutOfBounds> {
+        if addr as usize >= RAM_SIZE - 1 {
+            return Err(OutOfBounds {
+                index: addr as usize,
+                len: RAM_SIZE - 1,
+            });
+        }
+
+        let addr = addr as usize;
+        self.ram[addr..addr + 2].copy_from_slice(&op.to_be_bytes());
+        Ok(())
     }
 
     // cpu fetch operation
     fn fetch(&mut self) -> u16 {
         // CHIP-8 opcodes are exactly 2 bytes
         let higher_byte = self.ram[self.pc as usize] as u16;
-        let lower_byte = self.ram[(self.pc + 1) as usize] as u16;
+        let lower_byte = self.ram[(self.pc as usize + 1) % RAM_SIZE] as u16;
 
         // store values in RAM as 8-bit values (fetch two and combine as Big Endian)
         //  - bitshift left `higher_byte` by 8 bytes (to convert to 8-bit)
@@ -132,7 +1095,7 @@ impl Emu {
         let op = (higher_byte << 8) | lower_byte;
 
         // proceed to next opcode
-        self.pc += 2;
+        self.advance_pc();
 
         op
     }
@@ -143,159 +1106,643 @@ impl Emu {
             self.dt -= 1;
         }
 
+        // playing the beep is up to the host (see `FrameOutcome::beeping`), so the timers behave
+        // identically whether or not there's any audio
         if self.st > 0 {
-            if self.st == 1 {
-                // BEEP! (not covered in the guide)
-            }
             self.st -= 1
         }
+
+        // timers run at 60 Hz so this also marks the end of a frame for flicker tracking and `display_wait`
+        self.frame_drawn = false;
+        self.last_flicker = self.flicker;
+        self.flicker = 0;
+        self.erased = [0; PIXEL_WORDS];
+
+        // taken out for the call so the hook can borrow the emulator
+        #[cfg(feature = "alloc")]
+        if let Some(mut hook) = self.frame_hook.take() {
+            hook(self);
+            self.frame_hook = Some(hook);
+        }
+    }
+
+    // run `hook` once per 60 Hz frame, right after `tick_timers` updates the timers
+    // meant for embedding: the host can render or inspect the machine without driving its own frame loop
+    // the hook only gets a shared reference so it can't tick the emulator (that would re-enter the frame)
+    // kept across `reset` since it's part of the host's setup
+    #[cfg(feature = "alloc")]
+    pub fn set_frame_hook(&mut self, hook: FrameHook) {
+        self.frame_hook = Some(hook);
+    }
+
+    // remove the hook installed with `set_frame_hook`
+    #[cfg(feature = "alloc")]
+    pub fn clear_frame_hook(&mut self) {
+        self.frame_hook = None;
+    }
+
+    // number of pixels that DRAW turned off and then back on during the last frame
+    // (i.e. before the last `tick_timers`); a high score means the game erases and redraws its sprites
+    // every frame, which is a good hint to turn on a phosphor-fade style display
+    pub fn flicker_score(&self) -> u32 {
+        self.last_flicker
+    }
+
+    // current sound timer value; the beep should play while this is above 0
+    pub fn sound_timer(&self) -> u8 {
+        self.st
+    }
+
+    // current delay timer value (what FX07 would read)
+    pub fn delay_timer(&self) -> u8 {
+        self.dt
+    }
+
+    // record a pixel that DRAW just toggled for `flicker_score` (only the first plane is tracked)
+    fn track_flicker(&mut self, index: usize) {
+        let (word, bit) = (index / 64, 1u64 << (index % 64));
+
+        if !self.screen[0][index] {
+            self.erased[word] |= bit;
+        } else if self.erased[word] & bit != 0 {
+            // turned back on after being erased earlier in the same frame
+            self.erased[word] &= !bit;
+            self.flicker = self.flicker.saturating_add(1);
+        }
     }
 
     // pass pointer to screen buffer array to frontend
+    // rows are `display_width` pixels wide; the size changes when SUPER-CHIP switches resolution
+    // this is the first plane, which is the only one CHIP-8 and SUPER-CHIP games draw to
     pub fn get_display(&self) -> &[bool] {
-        &self.screen
+        self.get_plane(0).unwrap()
+    }
+
+    // a copy of `get_display` to put back later with `set_screen`, e.g. around a transition effect
+    // much lighter than a snapshot when only the picture matters
+    #[cfg(feature = "alloc")]
+    pub fn get_screen_copy(&self) -> Vec<bool> {
+        self.get_display().to_vec()
+    }
+
+    // overwrite the first plane with `pixels`, laid out like `get_display`
+    // `pixels` must hold exactly `display_width() * display_height()` pixels for the current resolution
+    pub fn set_screen(&mut self, pixels: &[bool]) -> Result<(), SizeMismatch> {
+        let len = self.display_width() * self.display_height();
+        if pixels.len() != len {
+            return Err(SizeMismatch {
+                expected: len,
+                actual: pixels.len(),
+            });
+        }
+
+        self.screen[0][..len].copy_from_slice(pixels);
+        self.display_changed = true;
+        Ok(())
+    }
+
+    // one display plane, laid out like `get_display`; `None` if `plane >= plane_count()`
+    pub fn get_plane(&self, plane: usize) -> Option<&[bool]> {
+        let len = self.display_width() * self.display_height();
+        self.screen[..self.plane_count]
+            .get(plane)
+            .map(|buf| &buf[..len])
+    }
+
+    // the `plane_bits` of every pixel, laid out like `get_display`; ready to index a palette with
+    pub fn display_indices(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..self.display_width() * self.display_height()).map(|index| self.plane_bits(index))
+    }
+
+    // whether the pixel at (x, y) is lit in the first plane; `None` if it's off the current display
+    pub fn pixel(&self, x: usize, y: usize) -> Option<bool> {
+        self.pixel_index(x, y).map(|index| self.screen[0][index])
+    }
+
+    // light or clear the pixel at (x, y) in the first plane, returning whether it was lit before
+    // (so callers can detect collisions the way DRAW does); coordinates off the display are ignored
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) -> bool {
+        let Some(index) = self.pixel_index(x, y) else {
+            return false;
+        };
+
+        let lit = self.screen[0][index];
+        if lit != on {
+            self.screen[0][index] = on;
+            self.display_changed = true;
+        }
+        lit
+    }
+
+    // position of (x, y) in the 1D screen buffers, if it's on the current display
+    fn pixel_index(&self, x: usize, y: usize) -> Option<usize> {
+        let width = self.display_width();
+        (x < width && y < self.display_height()).then(|| x + width * y)
+    }
+
+    // the planes lit at pixel `index` of the display as a bitmask (bit 0 is the first plane)
+    // XO-CHIP frontends use this as an index into a palette of `1 << plane_count()` colors:
+    //  0 = background, 1 = first plane only, 2 = second plane only, 3 = both
+    pub fn plane_bits(&self, index: usize) -> u8 {
+        self.screen[..self.plane_count]
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (plane, buf)| bits | ((buf[index] as u8) << plane))
+    }
+
+    // current screen dimensions in pixels
+    pub fn display_width(&self) -> usize {
+        if self.hires {
+            HIRES_WIDTH
+        } else {
+            SCREEN_WIDTH
+        }
+    }
+
+    pub fn display_height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            SCREEN_HEIGHT
+        }
+    }
+
+    // whether SUPER-CHIP high resolution mode is active
+    pub fn is_hires(&self) -> bool {
+        self.hires
+    }
+
+    // switch SUPER-CHIP resolution; the screen is cleared since the old contents no longer line up
+    fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.display_changed = true;
+        self.screen = [[false; HIRES_WIDTH * HIRES_HEIGHT]; MAX_PLANES];
+    }
+
+    // SUPER-CHIP scroll operations, shifting the active screen region and filling the gap with blank pixels
+    // only the selected planes move (XO-CHIP)
+    fn scroll_down(&mut self, rows: usize) {
+        let (width, height) = (self.display_width(), self.display_height());
+        self.display_changed = true;
+        for plane in selected_planes(self.planes) {
+            let screen = &mut self.screen[plane];
+            for y in (0..height).rev() {
+                for x in 0..width {
+                    screen[x + width * y] = y >= rows && screen[x + width * (y - rows)];
+                }
+            }
+        }
+    }
+
+    fn scroll_right(&mut self, cols: usize) {
+        let (width, height) = (self.display_width(), self.display_height());
+        self.display_changed = true;
+        for plane in selected_planes(self.planes) {
+            let screen = &mut self.screen[plane];
+            for y in 0..height {
+                for x in (0..width).rev() {
+                    screen[x + width * y] = x >= cols && screen[x - cols + width * y];
+                }
+            }
+        }
+    }
+
+    fn scroll_left(&mut self, cols: usize) {
+        let (width, height) = (self.display_width(), self.display_height());
+        self.display_changed = true;
+        for plane in selected_planes(self.planes) {
+            let screen = &mut self.screen[plane];
+            for y in 0..height {
+                for x in 0..width {
+                    screen[x + width * y] = x + cols < width && screen[x + cols + width * y];
+                }
+            }
+        }
+    }
+
+    // expand the screen buffer into packed RGBA bytes (4 per pixel) for image encoders and textures
+    #[cfg(feature = "alloc")]
+    pub fn display_as_rgba(&self, fg: [u8; 4], bg: [u8; 4]) -> Vec<u8> {
+        let display = self.get_display();
+        let mut rgba = Vec::with_capacity(display.len() * 4);
+        for pixel in display.iter() {
+            rgba.extend_from_slice(if *pixel { &fg } else { &bg });
+        }
+
+        rgba
+    }
+
+    // write the display into a caller-owned buffer as one ARGB (0xAARRGGBB) value per pixel
+    // lets renderers reuse the same buffer every frame instead of allocating
+    // `buf` must hold exactly `display_width() * display_height()` pixels
+    pub fn render_into(&self, buf: &mut [u32], fg: u32, bg: u32) -> Result<(), SizeMismatch> {
+        let display = self.get_display();
+        if buf.len() != display.len() {
+            return Err(SizeMismatch {
+                expected: display.len(),
+                actual: buf.len(),
+            });
+        }
+
+        for (out, pixel) in buf.iter_mut().zip(display.iter()) {
+            *out = if *pixel { fg } else { bg };
+        }
+
+        Ok(())
+    }
+
+    // cheap fingerprint of the whole machine for test assertions and netplay desync detection
+    // a single differing register, stack entry, RAM byte, or pixel produces a different hash
+    pub fn state_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        let mut fold = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+
+        fold(&self.pc.to_be_bytes());
+        fold(&self.i_reg.to_be_bytes());
+        fold(&self.sp.to_be_bytes());
+        fold(&self.v_reg);
+        fold(&[self.dt, self.st]);
+        for entry in self.stack[..self.stack_size].iter() {
+            fold(&entry.to_be_bytes());
+        }
+        fold(&self.ram);
+        // unused planes are always blank; leaving them out keeps hashes from before `set_plane_count` valid
+        for pixel in self.screen[..self.plane_count.max(NUM_PLANES)]
+            .iter()
+            .flatten()
+        {
+            fold(&[*pixel as u8]);
+        }
+        fold(&[self.hires as u8, self.planes]);
+        for key in self.keys.iter() {
+            fold(&[*key as u8]);
+        }
+
+        hash
     }
 
     // handle keypress
-    pub fn keypress(&mut self, index: usize, pressed: bool) {
+    // returns false (and changes nothing) if `index` isn't a CHIP-8 key
+    // a key released before any instruction ran since it was pressed stays down for one more instruction,
+    // so a quick tap between two frames still reaches EX9E / EXA1 / FX0A
+    pub fn keypress(&mut self, index: usize, pressed: bool) -> bool {
         // frontend handles key presses and sends it to this function
         // write the caught keypresses into the `keys` array
         // handles both keyup and keydown (toggles `pressed` to true or false accordingly)
-        // `index` needs to be under 16 or rust will panic (assume its correct here; handle in the frontend)
-        self.keys[index] = pressed;
+        // `index` has to be under 16; anything else is ignored rather than panicking
+        let Some(key) = self.keys.get_mut(index) else {
+            return false;
+        };
+
+        let bit = 1 << index;
+        if pressed {
+            // a held key auto-repeating doesn't count as a new press
+            if !*key {
+                self.presses = self.presses.wrapping_add(1);
+                self.press_order[index] = self.presses;
+            }
+            *key = true;
+            self.fresh_keys |= bit;
+            self.late_releases &= !bit;
+        } else if self.fresh_keys & bit != 0 {
+            self.late_releases |= bit;
+        } else {
+            *key = false;
+        }
+        true
+    }
+
+    // queue a key press or release to be applied between instructions instead of right away
+    // queued events go in one per instruction, oldest first, starting with the next `tick`; so a press and
+    // release that both arrive within one frame are still seen in order (e.g. by FX0A) rather than collapsing
+    // into whatever the key ends up as. `keys` just reflects the events applied so far
+    // returns false (and queues nothing) if `index` isn't a CHIP-8 key or the queue is full (`KEY_QUEUE_LEN` events waiting)
+    pub fn queue_key_event(&mut self, index: usize, pressed: bool) -> bool {
+        if index >= NUM_KEYS || self.queued_keys == KEY_QUEUE_LEN {
+            return false;
+        }
+
+        self.key_queue[self.queued_keys] = InputEvent {
+            cycle: self.cycles,
+            key: index as u8,
+            pressed,
+        };
+        self.queued_keys += 1;
+        true
+    }
+
+    // events from `queue_key_event` that haven't been applied yet, oldest first
+    // each one's `cycle` is the instruction count when it was queued, so they can be recorded for `replay_and_hash`
+    pub fn queued_key_events(&self) -> &[InputEvent] {
+        &self.key_queue[..self.queued_keys]
+    }
+
+    // feed the oldest queued event to `keypress` before the next instruction runs
+    fn apply_queued_key(&mut self) {
+        if self.queued_keys == 0 {
+            return;
+        }
+
+        let event = self.key_queue[0];
+        self.key_queue.copy_within(1..self.queued_keys, 0);
+        self.queued_keys -= 1;
+        self.keypress(event.key as usize, event.pressed);
+    }
+
+    // an instruction has now seen every pending press; apply the releases that were held back
+    fn latch_keys(&mut self) {
+        for (i, key) in self.keys.iter_mut().enumerate() {
+            if self.late_releases & (1 << i) != 0 {
+                *key = false;
+            }
+        }
+        self.fresh_keys = 0;
+        self.late_releases = 0;
+    }
+
+    // the 16 hex digit sprites as they currently are in RAM (`FONT_CHAR_SIZE` bytes each, starting at
+    // `FONTSET_START`); a ROM can overwrite them, so this is what FX29 would point at right now
+    pub fn fontset(&self) -> &[u8] {
+        &self.ram[FONTSET_START..FONTSET_START + FONTSET_SIZE]
+    }
+
+    // the last sprite DRAW put on the screen since the last `reset`, e.g. for outlining it in a debugger
+    pub fn last_draw(&self) -> Option<DrawInfo> {
+        self.last_draw
+    }
+
+    // the opcode the last `tick` executed since the last `reset`, e.g. for a "last:" status line
+    // the opposite of `peek_op`, which looks at the one about to run
+    // an unknown opcode counts too, even though `tick` reported it instead of running it
+    pub fn last_opcode(&self) -> Option<u16> {
+        self.last_op
+    }
+
+    // (address, opcode) of up to the last `RECENT_OPS` instructions `tick` executed, oldest first
+    // e.g. for a crash report showing how the game got to an error; unknown opcodes count like in
+    // `last_opcode`, and the history starts over on `reset`
+    pub fn recent_opcodes(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        let len = self.num_recent.min(RECENT_OPS as u64);
+        let start = self.num_recent - len;
+        (start..self.num_recent).map(|n| self.recent[(n % RECENT_OPS as u64) as usize])
+    }
+
+    // `last_opcode` disassembled, e.g. "DRAW V1 V2 5"; `None` if it isn't an instruction
+    #[cfg(feature = "alloc")]
+    pub fn last_disasm(&self) -> Option<String> {
+        decode(self.last_op?).map(|instr| instr.to_string())
+    }
+
+    // whether CHIP-8 key `index` (0x0 - 0xF) is held down; out of range keys are never pressed
+    pub fn is_key_pressed(&self, index: usize) -> bool {
+        self.keys.get(index).copied().unwrap_or(false)
+    }
+
+    // the keys held down right now, lowest first, e.g. for an input overlay
+    pub fn pressed_keys(&self) -> impl Iterator<Item = usize> + '_ {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(|(_, pressed)| **pressed)
+            .map(|(index, _)| index)
+    }
+
+    // every key's state in one value: bit N is set while key N is held
+    pub fn key_mask(&self) -> u16 {
+        self.keys
+            .iter()
+            .enumerate()
+            .fold(0, |mask, (i, pressed)| mask | ((*pressed as u16) << i))
     }
 
     // load ROM file into RAM
-    pub fn load(&mut self, data: &[u8]) {
+    // ROMs that don't fit between 0x200 and the end of RAM are rejected without touching memory,
+    // and so are empty ones (which would otherwise just run the zeroed RAM as NOPs forever)
+    pub fn load(&mut self, data: &[u8]) -> Result<(), LoadError> {
+        if data.is_empty() {
+            return Err(LoadError::Empty);
+        }
         let start = START_ADDR as usize;
         let end = (START_ADDR as usize) + data.len();
+        if end > RAM_SIZE {
+            return Err(LoadError::TooLarge {
+                len: data.len(),
+                max: RAM_SIZE - start,
+            });
+        }
+
         // copy all values from input `data` and slice it into RAM beginning at 0x200 (i.e. `START_ADDR`)
         self.ram[start..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    // testing convenience: write a single opcode at pc (pc doesn't move) so the next `tick` runs it
+    // e.g. `emu.load_opcode(0x8124); emu.tick()` instead of assembling a whole ROM for one instruction
+    // with pc on 0xFFF the low byte goes to 0x000, which is where `fetch` looks for it
+    pub fn load_opcode(&mut self, op: u16) {
+        let pc = self.pc as usize;
+        let [high, low] = op.to_be_bytes();
+        self.ram[pc] = high;
+        self.ram[(pc + 1) % RAM_SIZE] = low;
+    }
+
+    // cpu decode operation
+    fn execute(&mut self, op: u16) -> Result<(), ExecError> {
+        self.last_op = Some(op);
+        self.recent[(self.num_recent % RECENT_OPS as u64) as usize] = (self.op_addr(), op);
+        self.num_recent += 1;
+        // opcodes that don't decode, or belong to another variant, are handed back to the frontend
+        match decode(op) {
+            Some(Decoded::SpriteHeight { .. }) if !self.tall_sprites => {
+                Err(ExecError::UnknownOpcode {
+                    pc: self.op_addr(),
+                    op,
+                })
+            }
+            Some(instr) if self.variant.includes(instr.variant()) => self.execute_decoded(instr),
+            _ => Err(ExecError::UnknownOpcode {
+                pc: self.op_addr(),
+                op,
+            }),
+        }
     }
 
     // cpu execute operation
-    fn execute(&mut self, op: u16) {
-        let digit1 = (op & 0xF000) >> 12;
-        let digit2 = (op & 0x0F00) >> 8;
-        let digit3 = (op & 0x00F0) >> 4;
-        let digit4 = op & 0x000F;
+    fn execute_decoded(&mut self, instr: Decoded) -> Result<(), ExecError> {
+        if let Some(counts) = self.histogram.as_mut() {
+            counts[instr.id()] += 1;
+        }
+        // a zero cost would let a frame run forever
+        self.tick_cost = self.costs.map_or(1, |costs| costs[instr.id()].max(1));
 
-        match (digit1, digit2, digit3, digit4) {
+        match instr {
             // NOP: 0x0000 - no operation
-            (0, 0, 0, 0) => return,
+            Decoded::Nop => (),
 
             // CLS: 0x00E0 - clear screen
-            (0, 0, 0xE, 0) => {
-                self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+            Decoded::Cls => {
+                // XO-CHIP only clears the selected planes
+                self.display_changed = true;
+                for plane in selected_planes(self.planes) {
+                    self.screen[plane] = [false; HIRES_WIDTH * HIRES_HEIGHT];
+                }
+            }
+
+            // SCROLL DOWN N: 0x00CN - scroll the screen down N rows (SUPER-CHIP)
+            Decoded::ScrollDown { n } => {
+                self.scroll_down(n as usize);
+            }
+
+            // SCROLL RIGHT: 0x00FB - scroll the screen right 4 pixels (SUPER-CHIP)
+            Decoded::ScrollRight => {
+                self.scroll_right(4);
+            }
+
+            // SCROLL LEFT: 0x00FC - scroll the screen left 4 pixels (SUPER-CHIP)
+            Decoded::ScrollLeft => {
+                self.scroll_left(4);
+            }
+
+            // LORES: 0x00FE - switch to 64x32 (SUPER-CHIP)
+            Decoded::Lores => {
+                self.set_hires(false);
+            }
+
+            // HIRES: 0x00FF - switch to 128x64 (SUPER-CHIP)
+            Decoded::Hires => {
+                self.set_hires(true);
             }
 
             // RET: 0x00EE - return from subroutine
-            (0, 0, 0xE, 0xE) => {
+            Decoded::Ret => {
                 // subroutine is like a jump but is expected to complete at some point (i.e. need to return to entry at some point)
                 //  - store current address in stack
                 //  - pop from stack when we need to return
-                let ret_addr = self.pop();
+                let ret_addr = self.pop()?;
                 self.pc = ret_addr;
             }
 
             // JMP NNN: 0x1NNN - jump to given address
-            (1, _, _, _) => {
-                let nnn = op & 0xFFF;
+            Decoded::Jmp { nnn } => {
                 self.pc = nnn;
             }
 
             // CALL NNN: 0x2NNN - call subroutine
-            (2, _, _, _) => {
-                let nnn = op & 0xFFF;
-
+            Decoded::Call { nnn } => {
                 // add current address to stack
-                self.push(self.pc);
+                self.push(self.pc)?;
                 // move pc to address
                 self.pc = nnn;
             }
 
             // SKIP VX == NN: 0x3XNN - skip next if VX == NN
-            (3, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
+            Decoded::SkipEqNN { x, nn } => {
+                let x = x as usize;
 
                 // skip to next operation if VX == NN
                 if self.v_reg[x] == nn {
-                    self.pc += 2;
+                    self.advance_pc();
                 }
             }
             // SKIP VX != NN: 0x4XNN - skip next if VX != NN
-            (4, _, _, _) => {
-                let x: usize = digit2 as usize;
-                let nn: u8 = (op & 0xFF) as u8;
+            Decoded::SkipNeNN { x, nn } => {
+                let x = x as usize;
 
                 // skip to next operation if VX != NN
                 if self.v_reg[x] != nn {
-                    self.pc += 2;
+                    self.advance_pc();
                 }
             }
 
             // SKIP VX == VY: 0x5XY0 - skip next if VX == VY
-            (5, _, _, 0) => {
+            Decoded::SkipEqVY { x, y } => {
                 // least significant digit is not used in this operation
-                // opcode requires it to be 0
+                // opcode requires it to be 0 (checked by `decode`)
 
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+                let x = x as usize;
+                let y = y as usize;
                 if self.v_reg[x] == self.v_reg[y] {
-                    self.pc += 2;
+                    self.advance_pc();
                 }
             }
 
             // VX = NN: 0x6XNN - assign VX = NN
-            (6, _, _, _) => {
-                let x: usize = digit2 as usize;
-                let nn: u8 = (op & 0xFF) as u8;
-                self.v_reg[x] = nn;
+            Decoded::SetNN { x, nn } => {
+                self.v_reg[x as usize] = nn;
             }
 
             // VX += NN: 0x7XNN - add NN to VX register
-            (7, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
+            Decoded::AddNN { x, nn } => {
+                let x = x as usize;
 
                 // rust will panic in the event of an overflow
                 // `wrapping_add` will wrap the value around 0 in the event of an overflow
+                // wrapping is correct CHIP-8 behavior, but often unintended so `debug_checks` reports it
+                #[cfg(feature = "debug_checks")]
+                if self.v_reg[x].checked_add(nn).is_none() {
+                    self.emit(DebugEvent::AddOverflow {
+                        pc: self.op_addr(),
+                        x: x as u8,
+                        value: self.v_reg[x],
+                        nn,
+                    });
+                }
                 self.v_reg[x] = self.v_reg[x].wrapping_add(nn);
             }
 
             // VX = VY: 0x8XY0 - assign VX to value in VY
-            (8, _, _, 0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::Set { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
                 self.v_reg[x] = self.v_reg[y];
             }
 
             // VX |= VY: 0x8XY1 - bitwise OR
-            (8, _, _, 1) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::Or { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
                 self.v_reg[x] |= self.v_reg[y];
+                if self.quirks.vf_reset {
+                    self.v_reg[0xF] = 0;
+                }
             }
 
             // VX &= VY: 0x8XY2 - bitwise AND
-            (8, _, _, 2) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::And { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
                 self.v_reg[x] &= self.v_reg[y];
+                if self.quirks.vf_reset {
+                    self.v_reg[0xF] = 0;
+                }
             }
 
             // VX ^= VY: 0x8XY3 - bitwise XOR
-            (8, _, _, 3) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::Xor { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
                 self.v_reg[x] ^= self.v_reg[y];
+                if self.quirks.vf_reset {
+                    self.v_reg[0xF] = 0;
+                }
             }
 
+            // vf_order: the arithmetic ops below (8XY4 / 8XY5 / 8XY6 / 8XY7 / 8XYE) all follow the same order
+            //  1. compute the result and the flag from the original register values
+            //  2. write VX
+            //  3. write VF
+            // so when X is 0xF the flag wins and VF ends up holding the carry / borrow / shifted-out bit
+            // interpreters disagree on this; writing VF last matches the original COSMAC VIP
+
             // VX += VY: 0x8XY4 - addition assignment of VX and VY
-            (8, _, _, 4) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::Add { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
 
                 // this operation could potentially overflow
                 let (new_vx, carry) = self.v_reg[x].overflowing_add(self.v_reg[y]);
@@ -309,9 +1756,9 @@ impl Emu {
             }
 
             // VX -= VY: 0x8XY5 - subtraction assignment of VX and VY
-            (8, _, _, 5) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::Sub { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
 
                 // same idea as VX += VY but could potentially underflow
                 let (new_vx, borrow) = self.v_reg[x].overflowing_sub(self.v_reg[y]);
@@ -323,20 +1770,27 @@ impl Emu {
             }
 
             // VX >>= 1: 0x8XY6 - bitwise right shift on VX
-            (8, _, _, 6) => {
-                let x = digit2 as usize;
+            Decoded::Shr { x, y } => {
+                let x = x as usize;
+                // the COSMAC VIP shifts VY into VX
+                let src = if self.quirks.shift_uses_vy {
+                    self.v_reg[y as usize]
+                } else {
+                    self.v_reg[x]
+                };
                 // catch dropped least-significant bit
-                let lsb = self.v_reg[x] & 1;
+                let lsb = src & 1;
+                let new_vx = src >> 1;
 
-                self.v_reg[x] >>= 1;
-                // store least-significant bit in flag register
+                self.v_reg[x] = new_vx;
+                // store least-significant bit in flag register (after VX, see vf_order)
                 self.v_reg[0xF] = lsb;
             }
 
             // VX = VY - VX: 0x8XY7 - subtraction assignment of VX and VY with operands reversed
-            (8, _, _, 7) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::SubN { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
 
                 let (new_vx, borrow) = self.v_reg[y].overflowing_sub(self.v_reg[x]);
                 let new_vf = if borrow { 0 } else { 1 };
@@ -345,145 +1799,257 @@ impl Emu {
                 self.v_reg[0xF] = new_vf;
             }
 
-            // VX <<= 1: 0x0XYE - bitwise left shift on VX
-            (8, _, _, 0xE) => {
-                let x = digit2 as usize;
+            // VX <<= 1: 0x8XYE - bitwise left shift on VX
+            Decoded::Shl { x, y } => {
+                let x = x as usize;
+                // the COSMAC VIP shifts VY into VX, same as 8XY6
+                let src = if self.quirks.shift_uses_vy {
+                    self.v_reg[y as usize]
+                } else {
+                    self.v_reg[x]
+                };
                 // catch dropped most-significant bit
-                let msb = (self.v_reg[x] >> 7) & 1;
+                let msb = (src >> 7) & 1;
+                let new_vx = src << 1;
 
-                self.v_reg[x] <<= 1;
-                // store most-significant bit in flag register
+                self.v_reg[x] = new_vx;
+                // store most-significant bit in flag register (after VX, see vf_order)
                 self.v_reg[0xF] = msb;
             }
 
             // SKIP VX != VY: 0x9XY0 - skip if VX != VY
-            (9, _, _, 0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            Decoded::SkipNeVY { x, y } => {
+                let x = x as usize;
+                let y = y as usize;
 
                 if self.v_reg[x] != self.v_reg[y] {
-                    self.pc += 2;
+                    self.advance_pc();
                 }
             }
 
             // I = NNN: 0xANNN - assign I-register to 0xNNN
-            (0xA, _, _, _) => {
-                let nnn = op & 0xFFF;
+            Decoded::SetI { nnn } => {
                 self.i_reg = nnn;
             }
 
             // JMP V0 + NNN: 0xBNNN - jump to V0 + 0xNNN
-            (0xB, _, _, _) => {
-                let nnn = op & 0xFFF;
-                self.pc = (self.v_reg[0] as u16) + nnn;
+            Decoded::JmpV0 { nnn } => {
+                // SUPER-CHIP reads this as BXNN and adds VX instead
+                let x = if self.quirks.jump_uses_vx {
+                    (nnn >> 8) as usize
+                } else {
+                    0
+                };
+                // only 12 address bits, so V0 = 0xFF with 0xFFF lands back near the start of RAM
+                self.pc = ((self.v_reg[x] as u16) + nnn) % RAM_SIZE as u16;
             }
 
             // VX = rand() & NN: 0xCXNN - random number generator
-            (0xC, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
-                let rng: u8 = random();
+            Decoded::Rand { x, nn } => {
+                let x = x as usize;
+                let rng = self.random_byte();
 
                 // CHIP-8 rng AND's the value with the given 0xNN value
                 self.v_reg[x] = rng & nn;
             }
 
             // DRAW: 0xDXYN - draw sprite at (X, Y) of height N
-            (0xD, _, _, _) => {
+            Decoded::Draw { x, y, n } => {
                 // overview:
                 //  - CHIP-8 sprites are always 8 pixels wide but can be between 1 to 16 pixels tall
                 //  - the height is specified by the `N` value in the opcode
                 //  - sprites are stored row-by-row beginning at the address stored in the I-register
                 //  - if any pixel is flipped from black to white (or vice-versa) the VF register is set and cleared
+                //  - SUPER-CHIP treats N = 0 as a 16x16 sprite stored as two bytes per row
+                //  - XO-CHIP draws to every selected plane in turn, each with its own copy of the sprite data
+
+                // the COSMAC VIP waits for the display interrupt before drawing
+                // rewind and retry until the next frame if a sprite was already drawn in this one
+                if self.quirks.display_wait {
+                    if self.frame_drawn {
+                        self.pc = self.op_addr();
+                        return Ok(());
+                    }
+                    self.frame_drawn = true;
+                }
+
+                let (width, height) = (self.display_width(), self.display_height());
+                let schip = self.variant == Variant::SuperChip;
+                let clip = self.quirks.clip_sprites;
+                let clip_collides = clip && self.quirks.clip_counts_collision;
+                // the bottom edge can wrap in hires while still clipping in lores
+                let clip_bottom = clip && !(self.hires && self.quirks.hires_wraps_vertically);
 
                 // get the (x, y) coordinates of our sprite
-                let x_coord = self.v_reg[digit2 as usize] as u16;
-                let y_coord = self.v_reg[digit3 as usize] as u16;
-                // the last digit determines how many rows high the sprite is
-                let num_rows = digit4;
+                // the starting point always wraps onto the screen (an x of 70 is 6 in lores), like real
+                // interpreters; only the pixels after it are clipped or wrapped depending on `clip_sprites`
+                let x_coord = self.v_reg[x as usize] as usize % width;
+                let y_coord = self.v_reg[y as usize] as usize % height;
 
-                // keep track if any pixels were flipped
+                // the last digit determines how many rows high the sprite is
+                // (DXY0 is a SUPER-CHIP instruction, so plain CHIP-8 never gets here with N = 0)
+                let big = n == 0;
+                // a height set by FX1D (tall sprites extension) replaces N for this one sprite
+                let tall = self.sprite_height.take().filter(|_| !big);
+                let (num_cols, num_rows) = match tall {
+                    _ if big => (16, 16),
+                    Some(height) => (8, height as usize),
+                    None => (8, n as usize),
+                };
+                // bytes of sprite data per plane
+                let sprite_len = if big { 32 } else { num_rows };
+
+                self.tick_drew = true;
+                self.last_draw = Some(DrawInfo {
+                    x: x_coord as u8,
+                    y: y_coord as u8,
+                    width: num_cols as u8,
+                    height: num_rows as u8,
+                    addr: self.i_reg,
+                });
+
+                // keep track if any pixels were flipped, and of how many rows had a flip
                 let mut flipped = false;
+                let mut collided_rows = 0;
+
+                for (layer, plane) in selected_planes(self.planes).enumerate() {
+                    let sprite_addr = self.i_reg as usize + layer * sprite_len;
+
+                    for y_line in 0..num_rows {
+                        // the `clip_sprites` quirk cuts sprites off at the screen edges instead of wrapping them
+                        // with `clip_counts_collision` a row that falls off the bottom still counts towards VF
+                        if clip_bottom && y_coord + y_line >= height {
+                            if clip_collides {
+                                flipped = true;
+                                collided_rows += 1;
+                            }
+                            continue;
+                        }
+
+                        // determine which memory address the row's data is stored
+                        // tall sprites near the end of RAM wrap back around to 0x000 like real interpreters
+                        // pack the row into the high bits of a u16 so 8 and 16 pixel wide rows are handled alike
+                        let pixels = if big {
+                            let addr = (sprite_addr + y_line * 2) % RAM_SIZE;
+                            ((self.ram[addr] as u16) << 8) | self.ram[(addr + 1) % RAM_SIZE] as u16
+                        } else {
+                            let addr = (sprite_addr + y_line) % RAM_SIZE;
+                            (self.ram[addr] as u16) << 8
+                        };
+
+                        let mut row_flipped = false;
+                        for x_line in 0..num_cols {
+                            // use a mask to fetch the current pixel's bit. only flip if it is a 1
+                            if (pixels & (0x8000 >> x_line)) != 0 {
+                                if clip && x_coord + x_line >= width {
+                                    row_flipped |= clip_collides;
+                                    continue;
+                                }
+
+                                // sprites should wrap around the screen so apply a modulo
+                                let x = (x_coord + x_line) % width;
+                                let y = (y_coord + y_line) % height;
+
+                                // get pixel's index (always on screen after the modulo)
+                                let index = self.pixel_index(x, y).unwrap();
+                                // check if we're about to flip the pixel and set
+                                // only XOR drawing reports collisions
+                                let lit = self.screen[plane][index];
+                                let new = match self.draw_mode {
+                                    DrawMode::Xor => {
+                                        row_flipped |= lit;
+                                        !lit
+                                    }
+                                    DrawMode::Set => true,
+                                    DrawMode::Clear => false,
+                                };
+                                self.screen[plane][index] = new;
+                                if new != lit {
+                                    self.display_changed = true;
+                                    #[cfg(feature = "alloc")]
+                                    self.note_pixel(plane, index, lit);
+                                    if plane == 0 {
+                                        self.track_flicker(index);
+                                    }
+                                }
+                            }
+                        }
 
-                for y_line in 0..num_rows {
-                    // determine which memory address the row's data is stored
-                    let addr = self.i_reg + y_line as u16;
-                    let pixels = self.ram[addr as usize];
-
-                    for x_line in 0..8 {
-                        // use a mask to fetch the current pixel's bit. only flip if it is a 1
-                        if (pixels & (0b1000_0000 >> x_line)) != 0 {
-                            // sprites should wrap around the screen so apply a modulo
-                            let x = (x_coord + x_line) as usize % SCREEN_WIDTH;
-                            let y = (y_coord + y_line) as usize % SCREEN_HEIGHT;
-
-                            // get pixel's index
-                            // screen is a 1D array so calculate the index value accordingly
-                            let index = x + SCREEN_WIDTH * y;
-                            // check if we're about to flip the pixel and set
-                            flipped |= self.screen[index];
-                            self.screen[index] ^= true;
+                        if row_flipped {
+                            flipped = true;
+                            collided_rows += 1;
                         }
                     }
                 }
 
                 // populate the VF register
-                if flipped {
+                // SUPER-CHIP in hires mode reports the number of rows that collided (or were clipped, see above)
+                if schip && self.hires {
+                    self.v_reg[0xF] = collided_rows;
+                } else if flipped {
                     self.v_reg[0xF] = 1;
                 } else {
                     self.v_reg[0xF] = 0;
                 }
+
+                #[cfg(feature = "alloc")]
+                if self.v_reg[0xF] != 0 {
+                    self.emit(DebugEvent::Collision {
+                        pc: self.op_addr(),
+                        x: x_coord as u8,
+                        y: y_coord as u8,
+                        vf: self.v_reg[0xF],
+                    });
+                }
             }
 
             // SKIP KEY PRESS: 0xEX9E - skip if key pressed
-            (0xE, _, 9, 0xE) => {
-                let x = digit2 as usize;
+            Decoded::SkipKey { x } => {
+                let x = x as usize;
                 let vx = self.v_reg[x];
-                let key = self.keys[vx as usize];
+                // only the low nibble picks a key, like the VIP's keypad scan
+                let key = self.keys[(vx & 0xF) as usize];
 
                 // skip operation if key in VX is the key being pressed
                 if key {
-                    self.pc += 2;
+                    self.advance_pc();
                 }
             }
 
             // SKIP KEY RELEASE: 0xEXA1 - skip if key not pressed
-            (0xE, _, 0xA, 1) => {
-                let x = digit2 as usize;
+            Decoded::SkipNotKey { x } => {
+                let x = x as usize;
                 let vx = self.v_reg[x];
-                let key = self.keys[vx as usize];
+                let key = self.keys[(vx & 0xF) as usize];
 
                 // skip operation if key in VX is not the key being pressed
                 if !key {
-                    self.pc += 2;
+                    self.advance_pc();
                 }
             }
 
             // VX = DT: 0xFX07 - stores delay timer value in VX
-            (0xF, _, 0, 7) => {
+            Decoded::GetDelay { x } => {
                 // delay timer ticks automatically
                 // this instruction stores it so the value can be read
 
-                let x = digit2 as usize;
-                self.v_reg[x] = self.dt;
+                self.v_reg[x as usize] = self.dt;
             }
 
             // WAIT KEY: 0xFX0A - wait for key press
-            (0xF, _, 0, 0xA) => {
-                let x = digit2 as usize;
-                let mut pressed = false;
-
-                // loop through all the keys currently being pressed
-                for i in 0..self.keys.len() {
-                    if self.keys[i] {
-                        // break out of the loop if the key pressed is the given key
-                        self.v_reg[x] = i as u8;
-                        pressed = true;
-                        break;
-                    }
-                }
-
-                if !pressed {
+            Decoded::WaitKey { x } => {
+                let x = x as usize;
+
+                // with several keys held the tie-break depends on `key_priority`
+                let held = (0..NUM_KEYS).filter(|i| self.keys[*i]);
+                let key = match self.key_priority {
+                    KeyPriority::LowestIndex => held.min(),
+                    KeyPriority::MostRecent => held.max_by_key(|i| self.press_order[*i]),
+                };
+
+                if let Some(key) = key {
+                    self.v_reg[x] = key as u8;
+                } else {
                     // if the key isn't pressed we need to block execution
                     // redo the previous opcode
                     // we don't loop endlessly because we need to poll for potential new key presses
@@ -492,81 +2058,142 @@ impl Emu {
             }
 
             // DT = VX: 0xFX15 - assign delay timer to value in VX
-            (0xF, _, 1, 5) => {
+            Decoded::SetDelay { x } => {
                 // delay timer does not reset once it hits 0
                 // this operation allows us to change its value
-                let x = digit2 as usize;
-                self.dt = self.v_reg[x];
+                self.dt = self.v_reg[x as usize];
             }
 
             // ST = VX: 0xFX18 - assign sound timer to value in VX
-            (0xF, _, 1, 8) => {
-                let x = digit2 as usize;
-                self.st = self.v_reg[x];
+            Decoded::SetSound { x } => {
+                self.st = self.v_reg[x as usize];
             }
 
             // I += VX: 0xFX1E - increment I-register value by VX
-            (0xF, _, 1, 0xE) => {
-                let x = digit2 as usize;
-                let vx = self.v_reg[x] as u16;
+            Decoded::AddI { x } => {
+                let vx = self.v_reg[x as usize] as u16;
                 self.i_reg = self.i_reg.wrapping_add(vx);
             }
 
             // I = FONT: 0xFX29 - set I to font address
-            (0xF, _, 2, 9) => {
+            Decoded::Font { x } => {
                 // in the beginning, we stored every number sprite in the beginning of RAM
                 // each sprite is 8 pixels wide and 5 pixels tall
                 // thus, the RAM address for each sprite is its number * 5 (numbers from 0x0 to 0xF)
-                let x = digit2 as usize;
-                let c = self.v_reg[x] as u16;
-                // offset is conveniently 5 due to how we built the sprites initially
-                self.i_reg = c * 5;
+                let c = self.v_reg[x as usize] as usize;
+                // offset is conveniently 5 (`FONT_CHAR_SIZE`) due to how we built the sprites initially
+                self.i_reg = (FONTSET_START + FONT_CHAR_SIZE * c) as u16;
             }
 
             // BCD: 0xFX33 - convert hex number to pseudo-decimal number for display purposes
-            (0xF, _, 3, 3) => {
+            Decoded::Bcd { x } => {
                 // this a really naive BCD (binary-coded decimal) algorithm
-                //  - converts VX value to float to use division and modulo to get each decimal digit
-                //  - not fastest or shortest solution (easiest to understand)
+                //  - uses integer division and modulo to get each decimal digit
+                //  - float `floor` isn't available without std so stick to integers
 
-                let x = digit2 as usize;
-                let vx = self.v_reg[x] as f32;
+                self.check_write(self.i_reg as usize, 3)?;
+                let vx = self.v_reg[x as usize];
 
-                // fetch hundreds digit by dividing by 100 and tossing the decimal
-                let hundreds = (vx / 100.0).floor() as u8;
-                // fetch the tens digit by dividing by 10 and tossing the ones digit and the decimal
-                let tens = ((vx / 10.0) % 10.0).floor() as u8;
+                // fetch hundreds digit by dividing by 100 (integer division tosses the remainder)
+                let hundreds = vx / 100;
+                // fetch the tens digit by dividing by 10 and tossing the hundreds
+                let tens = (vx / 10) % 10;
                 // fetch the ones digit by tossing the hundreds and the tens
-                let ones = (vx % 10.0) as u8;
+                let ones = vx % 10;
 
                 // store the BCD with 3 bytes in the I-register
-                self.ram[self.i_reg as usize] = hundreds;
-                self.ram[(self.i_reg + 1) as usize] = tens;
-                self.ram[(self.i_reg + 2) as usize] = ones;
+                // digits that would land past the end of RAM wrap back around to 0x000, like sprite reads in DRAW
+                for (offset, digit) in [hundreds, tens, ones].into_iter().enumerate() {
+                    let addr = (self.i_reg as usize + offset) % RAM_SIZE;
+                    self.ram[addr] = digit;
+                    self.note_write(addr);
+                }
             }
 
             // STORE V0 - VX: 0xFX55 - populate registers V0 to VX (inclusive) into I-register
-            (0xF, _, 5, 5) => {
-                let x = digit2 as usize;
+            Decoded::Store { x } => {
+                let x = x as usize;
                 let i = self.i_reg as usize;
+                self.check_ram(i, x + 1)?;
+                self.check_write(i, x + 1)?;
 
                 // ..= is inclusive range
                 for index in 0..=x {
                     self.ram[i + index] = self.v_reg[index];
+                    self.note_write(i + index);
+                }
+
+                // the COSMAC VIP leaves I just past the stored registers
+                if self.quirks.increment_i {
+                    self.i_reg = self.i_reg.wrapping_add(x as u16 + 1);
                 }
             }
 
             // LOAD V0 - VX: 0xFX65 - load I-register contents into registers V0 to VX (inclusive)
-            (0xF, _, 6, 5) => {
-                let x = digit2 as usize;
+            Decoded::Load { x } => {
+                let x = x as usize;
                 let i = self.i_reg as usize;
+                self.check_ram(i, x + 1)?;
+
                 for index in 0..=x {
                     self.v_reg[index] = self.ram[i + index];
                 }
+
+                if self.quirks.increment_i {
+                    self.i_reg = self.i_reg.wrapping_add(x as u16 + 1);
+                }
+            }
+
+            // PLANE N: 0xFN01 - select which display planes later instructions draw to (XO-CHIP)
+            Decoded::Plane { n } => {
+                // bits past the last plane are ignored
+                self.planes = n & self.plane_mask();
+            }
+
+            // SPRH VX: 0xFX1D - the next DXYN draws a sprite VX rows tall (tall sprites extension)
+            Decoded::SpriteHeight { x } => {
+                // 0 goes back to using N; anything past the limit is clamped rather than reading a huge sprite
+                let height = self.v_reg[x as usize].min(MAX_SPRITE_HEIGHT);
+                self.sprite_height = (height > 0).then_some(height);
+            }
+
+            // SAVE FLAGS V0 - VX: 0xFX75 - copy registers V0 to VX (inclusive) into the RPL user flags (SUPER-CHIP)
+            // the HP48 only has 8 flags; like XO-CHIP all 16 registers fit here
+            Decoded::SaveFlags { x } => {
+                let x = x as usize;
+                self.rpl_flags[..=x].copy_from_slice(&self.v_reg[..=x]);
+            }
+
+            // STORE VX - VY: 0x5XY2 - store registers VX to VY (inclusive) in RAM starting at I (XO-CHIP)
+            // with Y < X the registers are stored in descending order; I is left alone either way
+            Decoded::StoreRange { x, y } => {
+                let i = self.i_reg as usize;
+                self.check_ram(i, x.abs_diff(y) as usize + 1)?;
+                self.check_write(i, x.abs_diff(y) as usize + 1)?;
+
+                for (offset, reg) in register_range(x, y).enumerate() {
+                    self.ram[i + offset] = self.v_reg[reg];
+                    self.note_write(i + offset);
+                }
+            }
+
+            // LOAD VX - VY: 0x5XY3 - load registers VX to VY (inclusive) from RAM starting at I (XO-CHIP)
+            Decoded::LoadRange { x, y } => {
+                let i = self.i_reg as usize;
+                self.check_ram(i, x.abs_diff(y) as usize + 1)?;
+
+                for (offset, reg) in register_range(x, y).enumerate() {
+                    self.v_reg[reg] = self.ram[i + offset];
+                }
             }
 
-            // base case: unimplemented op code; force rust to panic
-            (_, _, _, _) => unimplemented!("unimplemented opcode: {}", op),
+            // LOAD FLAGS V0 - VX: 0xFX85 - copy the RPL user flags back into registers V0 to VX (SUPER-CHIP)
+            Decoded::LoadFlags { x } => {
+                let x = x as usize;
+                self.v_reg[..=x].copy_from_slice(&self.rpl_flags[..=x]);
+            }
         }
+
+        Ok(())
     }
 }
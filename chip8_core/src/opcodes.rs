@@ -0,0 +1,356 @@
+use crate::{Decoded, Variant};
+
+// description of one instruction the emulator understands
+// an opcode `op` is this instruction when `op & mask == pattern`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    pub mask: u16,
+    pub pattern: u16,
+    pub mnemonic: &'static str,
+    pub description: &'static str,
+    // the first variant that supports the instruction
+    pub variant: Variant,
+}
+
+impl OpcodeInfo {
+    // whether `op` is an instance of this instruction
+    pub fn matches(&self, op: u16) -> bool {
+        op & self.mask == self.pattern
+    }
+}
+
+// shorthand for building the table below
+const fn info(
+    mask: u16,
+    pattern: u16,
+    mnemonic: &'static str,
+    description: &'static str,
+    variant: Variant,
+) -> OpcodeInfo {
+    OpcodeInfo {
+        mask,
+        pattern,
+        mnemonic,
+        description,
+        variant,
+    }
+}
+
+// number of entries in the table below
+pub const NUM_OPCODES: usize = 47;
+
+// every instruction `execute` handles, in the same order as its match arms
+// more specific patterns come first so the first match is the right one
+// `Decoded::id` indexes into this table so keep the two in sync
+const OPCODES: [OpcodeInfo; NUM_OPCODES] = [
+    info(0xFFFF, 0x0000, "NOP", "no operation", Variant::Chip8),
+    info(0xFFFF, 0x00E0, "CLS", "clear screen", Variant::Chip8),
+    info(
+        0xFFFF,
+        0x00EE,
+        "RET",
+        "return from subroutine",
+        Variant::Chip8,
+    ),
+    info(
+        0xFFF0,
+        0x00C0,
+        "SCROLL DOWN N",
+        "scroll the screen down N rows",
+        Variant::SuperChip,
+    ),
+    info(
+        0xFFFF,
+        0x00FB,
+        "SCROLL RIGHT",
+        "scroll the screen right 4 pixels",
+        Variant::SuperChip,
+    ),
+    info(
+        0xFFFF,
+        0x00FC,
+        "SCROLL LEFT",
+        "scroll the screen left 4 pixels",
+        Variant::SuperChip,
+    ),
+    info(
+        0xFFFF,
+        0x00FE,
+        "LORES",
+        "switch to 64x32",
+        Variant::SuperChip,
+    ),
+    info(
+        0xFFFF,
+        0x00FF,
+        "HIRES",
+        "switch to 128x64",
+        Variant::SuperChip,
+    ),
+    info(
+        0xF000,
+        0x1000,
+        "JMP NNN",
+        "jump to given address",
+        Variant::Chip8,
+    ),
+    info(
+        0xF000,
+        0x2000,
+        "CALL NNN",
+        "call subroutine",
+        Variant::Chip8,
+    ),
+    info(
+        0xF000,
+        0x3000,
+        "SKIP VX == NN",
+        "skip next if VX == NN",
+        Variant::Chip8,
+    ),
+    info(
+        0xF000,
+        0x4000,
+        "SKIP VX != NN",
+        "skip next if VX != NN",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0x5000,
+        "SKIP VX == VY",
+        "skip next if VX == VY",
+        Variant::Chip8,
+    ),
+    info(0xF000, 0x6000, "VX = NN", "assign VX = NN", Variant::Chip8),
+    info(
+        0xF000,
+        0x7000,
+        "VX += NN",
+        "add NN to VX register",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0x8000,
+        "VX = VY",
+        "assign VX to value in VY",
+        Variant::Chip8,
+    ),
+    info(0xF00F, 0x8001, "VX |= VY", "bitwise OR", Variant::Chip8),
+    info(0xF00F, 0x8002, "VX &= VY", "bitwise AND", Variant::Chip8),
+    info(0xF00F, 0x8003, "VX ^= VY", "bitwise XOR", Variant::Chip8),
+    info(
+        0xF00F,
+        0x8004,
+        "VX += VY",
+        "addition with carry in VF",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0x8005,
+        "VX -= VY",
+        "subtraction with borrow in VF",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0x8006,
+        "VX >>= 1",
+        "bitwise right shift on VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0x8007,
+        "VX = VY - VX",
+        "reversed subtraction with borrow in VF",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0x800E,
+        "VX <<= 1",
+        "bitwise left shift on VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0x9000,
+        "SKIP VX != VY",
+        "skip next if VX != VY",
+        Variant::Chip8,
+    ),
+    info(
+        0xF000,
+        0xA000,
+        "I = NNN",
+        "assign I-register to 0xNNN",
+        Variant::Chip8,
+    ),
+    info(
+        0xF000,
+        0xB000,
+        "JMP V0 + NNN",
+        "jump to V0 + 0xNNN",
+        Variant::Chip8,
+    ),
+    info(
+        0xF000,
+        0xC000,
+        "VX = rand() & NN",
+        "random number generator",
+        Variant::Chip8,
+    ),
+    info(
+        0xF00F,
+        0xD000,
+        "DRAW 16x16",
+        "draw 16x16 sprite at (VX, VY)",
+        Variant::SuperChip,
+    ),
+    info(
+        0xF000,
+        0xD000,
+        "DRAW",
+        "draw sprite at (VX, VY) of height N",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xE09E,
+        "SKIP KEY PRESS",
+        "skip next if key in VX pressed",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xE0A1,
+        "SKIP KEY RELEASE",
+        "skip next if key in VX not pressed",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF007,
+        "VX = DT",
+        "store delay timer value in VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF00A,
+        "WAIT KEY",
+        "wait for key press and store it in VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF015,
+        "DT = VX",
+        "assign delay timer to value in VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF018,
+        "ST = VX",
+        "assign sound timer to value in VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF01E,
+        "I += VX",
+        "increment I-register value by VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF029,
+        "I = FONT",
+        "set I to font address of digit in VX",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF033,
+        "BCD",
+        "store VX as 3 decimal digits at I",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF055,
+        "STORE V0 - VX",
+        "store registers V0 to VX in RAM at I",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF065,
+        "LOAD V0 - VX",
+        "load registers V0 to VX from RAM at I",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF001,
+        "PLANE N",
+        "select the display planes drawn to by CLS, DRAW, and the scrolls",
+        Variant::XoChip,
+    ),
+    info(
+        0xF0FF,
+        0xF01D,
+        "SPRH VX",
+        "make the next DXYN sprite VX rows tall (up to 32; extension, needs `set_tall_sprites`)",
+        Variant::Chip8,
+    ),
+    info(
+        0xF0FF,
+        0xF075,
+        "SAVE FLAGS V0 - VX",
+        "store registers V0 to VX in the RPL user flags",
+        Variant::SuperChip,
+    ),
+    info(
+        0xF0FF,
+        0xF085,
+        "LOAD FLAGS V0 - VX",
+        "load registers V0 to VX from the RPL user flags",
+        Variant::SuperChip,
+    ),
+    info(
+        0xF00F,
+        0x5002,
+        "STORE VX - VY",
+        "store registers VX to VY in RAM at I, counting down if Y < X",
+        Variant::XoChip,
+    ),
+    info(
+        0xF00F,
+        0x5003,
+        "LOAD VX - VY",
+        "load registers VX to VY from RAM at I, counting down if Y < X",
+        Variant::XoChip,
+    ),
+];
+
+// table of every supported instruction for documentation and feature-detection
+pub fn supported_opcodes() -> &'static [OpcodeInfo] {
+    &OPCODES
+}
+
+// a cost table for `EmuBuilder::instruction_costs`, indexed like `supported_opcodes()`
+// rough ratios from the COSMAC VIP, where clearing the screen or drawing a sprite takes several times as
+// long as register arithmetic; everything else costs 1
+pub fn weighted_costs() -> [u8; NUM_OPCODES] {
+    let mut costs = [1; NUM_OPCODES];
+    costs[Decoded::Cls.id()] = 4;
+    // both DXYN entries: the 8xN sprite and the SUPER-CHIP 16x16 one
+    costs[Decoded::Draw { x: 0, y: 0, n: 0 }.id()] = 8;
+    costs[Decoded::Draw { x: 0, y: 0, n: 1 }.id()] = 8;
+    costs
+}
@@ -0,0 +1,107 @@
+use crate::Variant;
+
+// behaviors that differ between CHIP-8 interpreters
+// games are usually written against one of them, so pick the preset matching the ROM's platform
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quirks {
+    // 8XY1 / 8XY2 / 8XY3 reset VF to 0 afterwards
+    pub vf_reset: bool,
+    // FX55 / FX65 leave I pointing just past the last register stored or loaded (I += X + 1)
+    pub increment_i: bool,
+    // DRAW waits for the next 60 Hz frame, so at most one sprite is drawn per frame
+    pub display_wait: bool,
+    // sprites are clipped at the screen edges instead of wrapping around to the other side
+    pub clip_sprites: bool,
+    // only matters with `clip_sprites`: whether the part of a sprite that's cut off counts towards VF
+    //  off: clipped pixels are dropped and VF only reports real collisions with lit pixels on screen
+    //  on: a lit sprite pixel cut off at the right edge counts as a collision, and so does every row
+    //      cut off at the bottom (in SUPER-CHIP hires mode, where VF counts rows, each one adds 1)
+    pub clip_counts_collision: bool,
+    // only matters with `clip_sprites`: in hires mode, rows past the bottom edge wrap to the top instead of
+    // being clipped (lores still clips them, and the right edge always follows `clip_sprites`)
+    pub hires_wraps_vertically: bool,
+    // 8XY6 / 8XYE shift VY and store the result in VX (otherwise VX is shifted in place)
+    pub shift_uses_vy: bool,
+    // BNNN jumps to XNN + VX instead of NNN + V0
+    pub jump_uses_vx: bool,
+}
+
+impl Quirks {
+    // the original COSMAC VIP interpreter
+    //  vf_reset, increment_i, display_wait, clip_sprites, shift_uses_vy: on
+    //  clip_counts_collision, hires_wraps_vertically, jump_uses_vx: off
+    pub const fn cosmac_vip() -> Self {
+        Self {
+            vf_reset: true,
+            increment_i: true,
+            display_wait: true,
+            clip_sprites: true,
+            clip_counts_collision: false,
+            hires_wraps_vertically: false,
+            shift_uses_vy: true,
+            jump_uses_vx: false,
+        }
+    }
+
+    // SUPER-CHIP 1.1 on the HP48
+    //  clip_sprites, clip_counts_collision, jump_uses_vx: on
+    //  vf_reset, increment_i, display_wait, hires_wraps_vertically, shift_uses_vy: off
+    pub const fn super_chip() -> Self {
+        Self {
+            vf_reset: false,
+            increment_i: false,
+            display_wait: false,
+            clip_sprites: true,
+            clip_counts_collision: true,
+            hires_wraps_vertically: false,
+            shift_uses_vy: false,
+            jump_uses_vx: true,
+        }
+    }
+
+    // Octo's XO-CHIP
+    //  increment_i, shift_uses_vy: on
+    //  vf_reset, display_wait, clip_sprites, clip_counts_collision, hires_wraps_vertically, jump_uses_vx: off
+    pub const fn xo_chip() -> Self {
+        Self {
+            vf_reset: false,
+            increment_i: true,
+            display_wait: false,
+            clip_sprites: false,
+            clip_counts_collision: false,
+            hires_wraps_vertically: false,
+            shift_uses_vy: true,
+            jump_uses_vx: false,
+        }
+    }
+
+    // what most modern interpreters (and most tutorials) do; the default for CHIP-8
+    //  every flag off
+    pub const fn modern() -> Self {
+        Self {
+            vf_reset: false,
+            increment_i: false,
+            display_wait: false,
+            clip_sprites: false,
+            clip_counts_collision: false,
+            hires_wraps_vertically: false,
+            shift_uses_vy: false,
+            jump_uses_vx: false,
+        }
+    }
+
+    // the preset `Emu::set_variant` switches to
+    pub const fn for_variant(variant: Variant) -> Self {
+        match variant {
+            Variant::Chip8 => Self::modern(),
+            Variant::SuperChip => Self::super_chip(),
+            Variant::XoChip => Self::xo_chip(),
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self::modern()
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{Emu, InputEvent};
+
+// a tiny ROM with the `state_hash` it's expected to finish with
+// an opcode change that alters behavior shows up as a different hash
+#[derive(Clone, Copy, Debug)]
+pub struct GoldenRom {
+    pub name: &'static str,
+    pub rom: &'static [u8],
+    pub inputs: &'static [InputEvent],
+    pub cycles: u64,
+    pub seed: u64,
+    pub hash: u64,
+}
+
+// timers are ticked every this many cycles, the same rate the desktop frontend runs at
+const CYCLES_PER_FRAME: u64 = 10;
+
+// run `rom` from power-on for `cycles` instructions and fingerprint the final machine state
+// everything is deterministic: CXNN uses the built-in generator seeded from `seed`, the timers are
+// ticked every `CYCLES_PER_FRAME` cycles, and `inputs` (sorted by cycle) are the only key changes
+// emulation stops early on an error; the hash then describes the machine as it was when it faulted
+pub fn replay_and_hash(rom: &[u8], inputs: &[InputEvent], cycles: u64, seed: u64) -> u64 {
+    let mut emu = Emu::new();
+    // fold the high half in so seeds that only differ there still diverge
+    emu.seed_rng((seed ^ (seed >> 32)) as u32);
+    // a ROM that doesn't fit leaves memory empty, which hashes differently from any real run
+    let _ = emu.load(rom);
+
+    let mut pending = inputs.iter().peekable();
+    for cycle in 0..cycles {
+        while let Some(event) = pending.next_if(|event| event.cycle <= cycle) {
+            emu.keypress(event.key as usize, event.pressed);
+        }
+
+        if emu.tick().is_err() {
+            break;
+        }
+
+        if (cycle + 1) % CYCLES_PER_FRAME == 0 {
+            emu.tick_timers();
+        }
+    }
+
+    emu.state_hash()
+}
+
+// regression fixtures: `replay_and_hash` on each of these should return its `hash`
+// if an intentional behavior change breaks one, re-run it and update the hash in the same commit
+pub const GOLDEN_ROMS: &[GoldenRom] = &[
+    GoldenRom {
+        name: "arithmetic",
+        rom: &[
+            0x60, 0x05, // V0 = 0x05
+            0x61, 0xFB, // V1 = 0xFB
+            0x80, 0x14, // V0 += V1 (carry)
+            0x62, 0x03, // V2 = 0x03
+            0x82, 0x15, // V2 -= V1 (borrow)
+            0x83, 0x26, // V3 >>= 1
+            0x84, 0x1E, // V4 <<= 1
+            0x85, 0x27, // V5 = V2 - V5
+            0x81, 0x21, // V1 |= V2
+            0x81, 0x32, // V1 &= V3
+            0x81, 0x43, // V1 ^= V4
+            0x7F, 0xFF, // VF += 0xFF (no carry flag)
+            0xC6, 0xFF, // V6 = rand
+            0xA3, 0x00, // I = 0x300
+            0xF6, 0x33, // BCD V6
+            0xF6, 0x55, // store V0 - V6
+            0x12, 0x20, // loop forever
+        ],
+        inputs: &[],
+        cycles: 64,
+        seed: 0xC0FFEE,
+        hash: 0x72EA_DC84_D4E9_6066,
+    },
+    GoldenRom {
+        name: "draw",
+        rom: &[
+            0x60, 0x0A, // V0 = 0x0A
+            0xF0, 0x29, // I = font(V0)
+            0x61, 0x3E, // V1 = 62 (wraps horizontally)
+            0x62, 0x1E, // V2 = 30 (wraps vertically)
+            0xD1, 0x25, // draw "A" across the corner
+            0xD1, 0x25, // erase it again (VF = 1)
+            0xD1, 0x25, // and redraw
+            0x00, 0xE0, // clear screen
+            0x63, 0x08, // V3 = 8
+            0xF3, 0x29, // I = font(V3)
+            0xD3, 0x35, // draw "8" at (8, 8)
+            0x12, 0x16, // loop forever
+        ],
+        inputs: &[],
+        cycles: 32,
+        seed: 0,
+        hash: 0x61AC_FF21_7525_C673,
+    },
+    GoldenRom {
+        name: "control flow",
+        rom: &[
+            0x60, 0x00, // 0x200: V0 = 0
+            0x22, 0x10, // 0x202: call 0x210
+            0x30, 0x04, // 0x204: skip if V0 == 4
+            0x12, 0x02, // 0x206: loop back to the call
+            0xF1, 0x0A, // 0x208: wait for a key into V1
+            0xB2, 0x10, // 0x20A: jump to 0x210 + V0 (0x214)
+            0x00, 0x00, // 0x20C: (unused)
+            0x00, 0x00, // 0x20E: (unused)
+            0x70, 0x01, // 0x210: V0 += 1
+            0x00, 0xEE, // 0x212: return
+            0xE1, 0x9E, // 0x214: skip if key V1 is down
+            0x12, 0x14, // 0x216: spin until it is
+            0xF2, 0x07, // 0x218: V2 = delay timer
+            0x12, 0x18, // 0x21A: loop forever
+        ],
+        inputs: &[
+            InputEvent {
+                cycle: 40,
+                key: 0x7,
+                pressed: true,
+            },
+            InputEvent {
+                cycle: 60,
+                key: 0x7,
+                pressed: false,
+            },
+        ],
+        cycles: 120,
+        seed: 0,
+        hash: 0x069E_A079_C94D_62A7,
+    },
+];
@@ -0,0 +1,187 @@
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::{
+    decode, Decoded, Emu, HIRES_HEIGHT, HIRES_WIDTH, MAX_PLANES, MAX_STACK_SIZE, NUM_REGS, RAM_SIZE,
+};
+
+// how many instructions can be undone; the oldest entries are dropped past this
+const UNDO_LIMIT: usize = 4096;
+
+type Screen = [[bool; HIRES_WIDTH * HIRES_HEIGHT]; MAX_PLANES];
+
+// what one instruction changed, enough to put the machine back the way it was
+// registers are always saved (they're tiny); RAM and the screen only where the instruction touched them
+struct UndoEntry {
+    pc: u16,
+    i_reg: u16,
+    sp: u16,
+    v_reg: [u8; NUM_REGS],
+    stack: [u16; MAX_STACK_SIZE],
+    dt: u8,
+    st: u8,
+    cycles: u64,
+    // the fraction of a timer tick built up so far, so automatic timers (`set_clock_hz`) rewind exactly
+    timer_acc: u32,
+    rng_state: u32,
+    // how far CXNN has got through `set_rng_override`'s list
+    rng_override_pos: usize,
+    hires: bool,
+    planes: u8,
+    frame_drawn: bool,
+    sprite_height: Option<u8>,
+    // (address, old value) for each byte written by FX55 / 5XY2 / FX33
+    ram: Vec<(u16, u8)>,
+    // (plane, index, old value) for each pixel DRAW changed
+    pixels: Vec<(u8, u16, bool)>,
+    // whole screen for instructions that rewrite all of it (CLS, scrolls, resolution changes)
+    screen: Option<Box<Screen>>,
+}
+
+// undo stack for `Emu::undo_instruction`
+pub(crate) struct UndoLog {
+    entries: VecDeque<UndoEntry>,
+    // the entry for the instruction currently executing
+    pending: Option<UndoEntry>,
+}
+
+impl UndoLog {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            pending: None,
+        }
+    }
+}
+
+// micro-rewind: stepping backwards one instruction at a time
+impl Emu {
+    // start or stop recording undo information; turning it off (or `reset`) throws away the history
+    // costs a small allocation per instruction while enabled, so it's meant for debuggers
+    pub fn set_micro_rewind(&mut self, enabled: bool) {
+        self.undo = enabled.then(UndoLog::new);
+    }
+
+    // put the machine back to how it was before the last instruction ran
+    // returns false if there's nothing to undo (rewind disabled, or history exhausted)
+    // key state, the RPL flags and diagnostics (histogram, flicker score) aren't rewound
+    pub fn undo_instruction(&mut self) -> bool {
+        let entry = match self.undo.as_mut().and_then(|log| log.entries.pop_back()) {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        self.pc = entry.pc;
+        self.i_reg = entry.i_reg;
+        self.sp = entry.sp;
+        self.v_reg = entry.v_reg;
+        self.stack = entry.stack;
+        self.dt = entry.dt;
+        self.st = entry.st;
+        self.cycles = entry.cycles;
+        self.timer_acc = entry.timer_acc;
+        self.rng_state = entry.rng_state;
+        self.rng_override_pos = entry.rng_override_pos;
+        self.hires = entry.hires;
+        self.planes = entry.planes;
+        self.frame_drawn = entry.frame_drawn;
+        self.sprite_height = entry.sprite_height;
+
+        for (addr, old) in entry.ram.into_iter().rev() {
+            self.ram[addr as usize] = old;
+        }
+        if !entry.pixels.is_empty() || entry.screen.is_some() {
+            self.display_changed = true;
+        }
+        for (plane, index, old) in entry.pixels.into_iter().rev() {
+            self.screen[plane as usize][index as usize] = old;
+        }
+        if let Some(screen) = entry.screen {
+            self.screen = *screen;
+        }
+
+        true
+    }
+
+    // snapshot what the instruction at pc is about to change (called by `tick` before it fetches)
+    pub(crate) fn begin_undo(&mut self) {
+        if self.undo.is_none() {
+            return;
+        }
+
+        let mut ram = Vec::new();
+        let mut screen = None;
+        let i = self.i_reg as usize;
+        match decode(self.peek_op()) {
+            Some(Decoded::Store { x }) => {
+                for addr in i..=i + x as usize {
+                    ram.extend(self.ram.get(addr).map(|old| (addr as u16, *old)));
+                }
+            }
+            Some(Decoded::StoreRange { x, y }) => {
+                for addr in i..=i + x.abs_diff(y) as usize {
+                    ram.extend(self.ram.get(addr).map(|old| (addr as u16, *old)));
+                }
+            }
+            // BCD wraps around the end of RAM, so the saved bytes have to as well
+            Some(Decoded::Bcd { .. }) => {
+                for addr in (i..i + 3).map(|addr| addr % RAM_SIZE) {
+                    ram.push((addr as u16, self.ram[addr]));
+                }
+            }
+            Some(
+                Decoded::Cls
+                | Decoded::ScrollDown { .. }
+                | Decoded::ScrollRight
+                | Decoded::ScrollLeft
+                | Decoded::Lores
+                | Decoded::Hires,
+            ) => screen = Some(Box::new(self.screen)),
+            _ => (),
+        }
+
+        let entry = UndoEntry {
+            pc: self.pc,
+            i_reg: self.i_reg,
+            sp: self.sp,
+            v_reg: self.v_reg,
+            stack: self.stack,
+            dt: self.dt,
+            st: self.st,
+            cycles: self.cycles,
+            timer_acc: self.timer_acc,
+            rng_state: self.rng_state,
+            rng_override_pos: self.rng_override_pos,
+            hires: self.hires,
+            planes: self.planes,
+            frame_drawn: self.frame_drawn,
+            sprite_height: self.sprite_height,
+            ram,
+            pixels: Vec::new(),
+            screen,
+        };
+        if let Some(log) = self.undo.as_mut() {
+            log.pending = Some(entry);
+        }
+    }
+
+    // remember a pixel's old value before DRAW changes it
+    pub(crate) fn note_pixel(&mut self, plane: usize, index: usize, old: bool) {
+        if let Some(entry) = self.undo.as_mut().and_then(|log| log.pending.as_mut()) {
+            entry.pixels.push((plane as u8, index as u16, old));
+        }
+    }
+
+    // keep the pending entry once the instruction has run successfully, or drop it if it failed
+    pub(crate) fn finish_undo(&mut self, ok: bool) {
+        if let Some(log) = self.undo.as_mut() {
+            if let Some(entry) = log.pending.take().filter(|_| ok) {
+                if log.entries.len() == UNDO_LIMIT {
+                    log.entries.pop_front();
+                }
+                log.entries.push_back(entry);
+            }
+        }
+    }
+}
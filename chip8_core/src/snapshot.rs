@@ -0,0 +1,117 @@
+use alloc::vec::Vec;
+
+use crate::{Emu, MAX_SPRITE_HEIGHT, PIXEL_WORDS, RAM_SIZE};
+
+// a copy of the machine's state for save states
+// configuration (variant, quirks, clock speed, breakpoints, hooks) isn't included: a state is restored
+// into an emulator that's already set up for the game
+// with the `serde` feature it can be serialized, e.g. to save it to a file
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmuState {
+    pc: u16,
+    ram: Vec<u8>,
+    // one full size (hires) buffer per plane
+    screen: Vec<Vec<bool>>,
+    v_reg: Vec<u8>,
+    i_reg: u16,
+    sp: u16,
+    stack: Vec<u16>,
+    keys: Vec<bool>,
+    dt: u8,
+    st: u8,
+    cycles: u64,
+    timer_acc: u32,
+    rng_state: u32,
+    hires: bool,
+    planes: u8,
+    frame_drawn: bool,
+    sprite_height: Option<u8>,
+}
+
+// save states
+impl Emu {
+    pub fn snapshot(&self) -> EmuState {
+        EmuState {
+            pc: self.pc,
+            ram: self.ram.to_vec(),
+            screen: self.screen.iter().map(|plane| plane.to_vec()).collect(),
+            v_reg: self.v_reg.to_vec(),
+            i_reg: self.i_reg,
+            sp: self.sp,
+            stack: self.stack.to_vec(),
+            keys: self.keys.to_vec(),
+            dt: self.dt,
+            st: self.st,
+            cycles: self.cycles,
+            timer_acc: self.timer_acc,
+            rng_state: self.rng_state,
+            hires: self.hires,
+            planes: self.planes,
+            frame_drawn: self.frame_drawn,
+            sprite_height: self.sprite_height,
+        }
+    }
+
+    // put the machine back the way it was when `state` was taken
+    // returns false (and changes nothing) if `state` doesn't fit this machine, e.g. a corrupt save file
+    // or one saved with more display planes than this emulator has
+    pub fn restore(&mut self, state: &EmuState) -> bool {
+        let fits = state.ram.len() == self.ram.len()
+            && state.screen.len() == self.screen.len()
+            && state
+                .screen
+                .iter()
+                .all(|plane| plane.len() == self.screen[0].len())
+            && state.v_reg.len() == self.v_reg.len()
+            && state.stack.len() == self.stack.len()
+            && state.keys.len() == self.keys.len()
+            && (state.sp as usize) <= self.stack_size
+            // anything `fetch` or RET could run off the end of RAM with
+            && (state.pc as usize) < RAM_SIZE
+            && state.stack.iter().all(|addr| (*addr as usize) < RAM_SIZE)
+            && state.planes & !self.plane_mask() == 0
+            && state
+                .sprite_height
+                .is_none_or(|height| (1..=MAX_SPRITE_HEIGHT).contains(&height));
+        if !fits {
+            return false;
+        }
+
+        self.pc = state.pc;
+        self.ram.copy_from_slice(&state.ram);
+        for (plane, saved) in self.screen.iter_mut().zip(state.screen.iter()) {
+            plane.copy_from_slice(saved);
+        }
+        self.v_reg.copy_from_slice(&state.v_reg);
+        self.i_reg = state.i_reg;
+        self.sp = state.sp;
+        self.stack.copy_from_slice(&state.stack);
+        self.keys.copy_from_slice(&state.keys);
+        self.dt = state.dt;
+        self.st = state.st;
+        self.cycles = state.cycles;
+        self.timer_acc = state.timer_acc;
+        self.rng_state = state.rng_state;
+        self.hires = state.hires;
+        self.planes = state.planes;
+        self.frame_drawn = state.frame_drawn;
+        self.sprite_height = state.sprite_height;
+
+        // bookkeeping that only makes sense for the timeline that was just replaced
+        self.fresh_keys = 0;
+        self.late_releases = 0;
+        self.queued_keys = 0;
+        self.last_draw = None;
+        self.last_op = None;
+        self.num_recent = 0;
+        self.erased = [0; PIXEL_WORDS];
+        self.flicker = 0;
+        self.last_flicker = 0;
+        if self.undo.is_some() {
+            self.set_micro_rewind(true);
+        }
+        self.display_changed = true;
+        true
+    }
+}
@@ -0,0 +1,64 @@
+// tiny hand-assembled ROMs for tests and examples, so nothing has to be loaded from disk
+// each one is meant to be read alongside its comments: they're also small worked examples of the opcodes
+
+// draws a 5x5 plus sign in the middle of the screen, then halts
+// exercises CLS, ANNN, DXYN, and reading sprite data that follows the code
+// afterwards the pixels (28..33, 14) and (30, 12..17) are lit and VF = 0
+pub const PLUS_SIGN: &[u8] = &[
+    0x00, 0xE0, // 0x200: clear screen
+    0xA2, 0x0C, // 0x202: I = 0x20C (the sprite below)
+    0x60, 0x1C, // 0x204: V0 = 28
+    0x61, 0x0C, // 0x206: V1 = 12
+    0xD0, 0x15, // 0x208: draw 5 rows at (V0, V1)
+    0x12, 0x0A, // 0x20A: halt (jump to self)
+    0x20, // 0x20C: ..#.....
+    0x20, //        ..#.....
+    0xF8, //        #####...
+    0x20, //        ..#.....
+    0x20, //        ..#.....
+];
+
+// counts up once a second using the delay timer, showing the count (mod 16) as a hex digit at (0, 0)
+// exercises FX15 / FX07 timer polling, 3XNN skips, FX29 font lookup, and a main loop built from jumps
+// the count is in V1; it needs the timers ticked at 60 Hz to advance
+pub const DELAY_COUNTER: &[u8] = &[
+    0x61, 0x00, // 0x200: V1 = 0
+    0x60, 0x3C, // 0x202: V0 = 60
+    0xF0, 0x15, // 0x204: DT = V0 (one second)
+    0xF0, 0x07, // 0x206: V0 = DT
+    0x30, 0x00, // 0x208: skip the next instruction if V0 == 0
+    0x12, 0x06, // 0x20A: not yet, keep polling
+    0x71, 0x01, // 0x20C: V1 += 1
+    0x62, 0x0F, // 0x20E: V2 = 0x0F
+    0x81, 0x22, // 0x210: V1 &= V2 (keep it a single hex digit)
+    0x00, 0xE0, // 0x212: clear screen
+    0xF1, 0x29, // 0x214: I = font(V1)
+    0x62, 0x00, // 0x216: V2 = 0
+    0xD2, 0x25, // 0x218: draw the digit at (0, 0)
+    0x12, 0x02, // 0x21A: start the next second
+];
+
+// runs each flag-setting arithmetic instruction once and copies VF somewhere it survives
+// exercises 8XY4 / 8XY5 / 8XYE / 8XY6 and the fact that VF is written after the result
+// when it halts: VE = 1 (add carried), VD = 1 (5 - 1 didn't borrow), VC = 0 (0 - 1 borrowed),
+// VB = 1 (0x81 << 1 shifted out a 1), VA = 1 (3 >> 1 shifted out a 1), V0 = 1
+// the shifts use VX as the source so the result is the same with or without the `shift_uses_vy` quirk
+pub const CARRY_FLAGS: &[u8] = &[
+    0x60, 0xFF, // 0x200: V0 = 0xFF
+    0x61, 0x01, // 0x202: V1 = 1
+    0x80, 0x14, // 0x204: V0 += V1 (0x00, carry)
+    0x8E, 0xF0, // 0x206: VE = VF
+    0x60, 0x05, // 0x208: V0 = 5
+    0x80, 0x15, // 0x20A: V0 -= V1 (4, no borrow)
+    0x8D, 0xF0, // 0x20C: VD = VF
+    0x60, 0x00, // 0x20E: V0 = 0
+    0x80, 0x15, // 0x210: V0 -= V1 (0xFF, borrow)
+    0x8C, 0xF0, // 0x212: VC = VF
+    0x60, 0x81, // 0x214: V0 = 0x81
+    0x80, 0x0E, // 0x216: V0 <<= 1 (0x02)
+    0x8B, 0xF0, // 0x218: VB = VF
+    0x60, 0x03, // 0x21A: V0 = 3
+    0x80, 0x06, // 0x21C: V0 >>= 1 (1)
+    0x8A, 0xF0, // 0x21E: VA = VF
+    0x12, 0x20, // 0x220: halt (jump to self)
+];
@@ -0,0 +1,45 @@
+#![cfg(feature = "asm")]
+
+use chip8_core::asm::*;
+use chip8_core::{decode, supported_opcodes, Decoded};
+
+#[test]
+fn encode_undoes_decode() {
+    for op in 0..=0xFFFF {
+        if let Some(instr) = decode(op) {
+            assert_eq!(instr.encode(), op, "0x{:04X}", op);
+        }
+    }
+    for info in supported_opcodes() {
+        assert!(info.matches(decode(info.pattern).unwrap().encode()));
+    }
+}
+
+#[test]
+fn builder_functions() {
+    assert_eq!(cls(), [0x00, 0xE0]);
+    assert_eq!(jmp(0x206), [0x12, 0x06]);
+    assert_eq!(call(0x300), [0x23, 0x00]);
+    assert_eq!(ld_vx_nn(0xA, 0x42), [0x6A, 0x42]);
+    assert_eq!(add_vx_nn(1, 2), [0x71, 0x02]);
+    assert_eq!(drw(1, 2, 5), [0xD1, 0x25]);
+    assert_eq!(
+        assemble(&[Decoded::Cls, Decoded::Ret]),
+        [0x00, 0xE0, 0x00, 0xEE]
+    );
+}
+
+#[cfg(feature = "test_roms")]
+#[test]
+fn assembles_the_plus_sign_rom() {
+    let code = [
+        cls(),
+        ld_i(0x20C),
+        ld_vx_nn(0, 28),
+        ld_vx_nn(1, 12),
+        drw(0, 1, 5),
+        jmp(0x20A),
+    ]
+    .concat();
+    assert_eq!(code, chip8_core::test_roms::PLUS_SIGN[..12]);
+}
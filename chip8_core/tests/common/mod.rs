@@ -0,0 +1,33 @@
+// helpers shared by the integration tests; not every test file uses all of them
+#![allow(dead_code)]
+
+use chip8_core::{Emu, Reg};
+
+// where `rom` puts the sprite data, well clear of the code
+pub const SPRITE_ADDR: u16 = 0x300;
+
+// a ROM with `words` as the code at 0x200 and `sprite` at `SPRITE_ADDR`
+pub fn rom(words: &[u16], sprite: &[u8]) -> Vec<u8> {
+    let sprite_at = (SPRITE_ADDR - 0x200) as usize;
+    let mut data = vec![0; sprite_at + sprite.len()];
+    for (i, word) in words.iter().enumerate() {
+        data[i * 2..i * 2 + 2].copy_from_slice(&word.to_be_bytes());
+    }
+    data[sprite_at..].copy_from_slice(sprite);
+    data
+}
+
+// a fresh emulator with `rom(words, sprite)` loaded
+pub fn emu_with(words: &[u16], sprite: &[u8]) -> Emu {
+    let mut emu = Emu::new();
+    emu.load(&rom(words, sprite)).unwrap();
+    emu
+}
+
+pub fn reg(x: u8) -> Reg {
+    Reg::new(x).unwrap()
+}
+
+pub fn vf(emu: &Emu) -> u8 {
+    emu.get_register(Reg::VF)
+}
@@ -0,0 +1,512 @@
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use chip8_core::{
+    decode, supported_opcodes, weighted_costs, Emu, EmuBuilder, ExecError, FrameOutcome, Quirks,
+    Reg, Variant, MAX_STACK_SIZE,
+};
+use common::*;
+
+#[test]
+fn ram_access_past_the_end_is_an_error() {
+    // I = 0xFFE, then a 3 byte STORE / LOAD / range store / range load
+    for op in [0xF255, 0xF265, 0x5022, 0x5023] {
+        let mut emu = emu_with(&[0xAFFE, op], &[]);
+        emu.set_variant(Variant::XoChip);
+        emu.tick().unwrap();
+
+        let result = catch_unwind(AssertUnwindSafe(|| emu.tick()));
+        assert_eq!(
+            result.expect("no panic"),
+            Err(ExecError::RamOutOfBounds {
+                pc: 0x202,
+                addr: 0xFFE
+            }),
+            "opcode 0x{:04X}",
+            op
+        );
+    }
+}
+
+#[test]
+fn ram_access_up_to_the_last_byte_is_fine() {
+    // I = 0xFFE, then a 2 byte STORE / LOAD / range store / range load
+    for op in [0xF155, 0xF165, 0x5012, 0x5013] {
+        let mut emu = emu_with(&[0xAFFE, op], &[]);
+        emu.set_variant(Variant::XoChip);
+        emu.run_cycles(2).unwrap();
+    }
+}
+
+#[test]
+fn pc_wraps_at_the_end_of_ram() {
+    // I = 0xFFF, V0 = 0x60, store V0 at 0xFFF, jump there; with the first font byte (0xF0) at 0x000
+    // that makes V0 = 0xF0 split across the end and the start of RAM
+    let mut emu = emu_with(&[0xAFFF, 0x6060, 0xF055, 0x1FFF], &[]);
+    let result = catch_unwind(AssertUnwindSafe(|| emu.run_cycles(5)));
+    assert!(result.expect("no panic").is_ok());
+    assert_eq!(emu.get_register(reg(0)), 0xF0);
+    assert_eq!(emu.get_pc(), 0x001);
+
+    // V0 = 0xFF, jump to V0 + 0xFFF
+    let mut emu = emu_with(&[0x60FF, 0xBFFF], &[]);
+    let result = catch_unwind(AssertUnwindSafe(|| emu.run_cycles(2)));
+    assert!(result.expect("no panic").is_ok());
+    assert_eq!(emu.get_pc(), 0x0FE);
+
+    // a skip (taken) and a plain instruction in the last two bytes
+    for (op, pc) in [(0x3000, 0x002), (0x6001, 0x000)] {
+        let mut emu = Emu::new();
+        emu.poke_opcode(0xFFE, op).unwrap();
+        emu.set_pc(0xFFE).unwrap();
+        let result = catch_unwind(AssertUnwindSafe(|| emu.tick()));
+        assert!(result.expect("no panic").is_ok());
+        assert_eq!(emu.get_pc(), pc, "opcode 0x{:04X}", op);
+    }
+}
+
+#[test]
+fn key_skips_only_use_the_low_nibble() {
+    // V0 = 0x13, skip if key 0x13 (key 3) is pressed, V1 = 1, V2 = 1
+    let mut emu = emu_with(&[0x6013, 0xE09E, 0x6101, 0x6201], &[]);
+    emu.keypress(3, true);
+    let result = catch_unwind(AssertUnwindSafe(|| emu.run_cycles(3)));
+    assert!(result.expect("no panic").is_ok());
+    assert_eq!(emu.get_register(reg(1)), 0);
+    assert_eq!(emu.get_register(reg(2)), 1);
+
+    // same with skip if not pressed, which shouldn't skip
+    let mut emu = emu_with(&[0x60F3, 0xE0A1, 0x6101], &[]);
+    emu.keypress(3, true);
+    let result = catch_unwind(AssertUnwindSafe(|| emu.run_cycles(3)));
+    assert!(result.expect("no panic").is_ok());
+    assert_eq!(emu.get_register(reg(1)), 1);
+}
+
+#[test]
+fn register_ranges_store_and_load_in_order() {
+    // V1 = 0x11, V2 = 0x22, V3 = 0x33, I = 0x400, store V1 - V3, then store V3 - V1 at 0x403
+    let mut emu = emu_with(
+        &[0x6111, 0x6222, 0x6333, 0xA400, 0x5132, 0xA403, 0x5312],
+        &[],
+    );
+    emu.set_variant(Variant::XoChip);
+    emu.run_cycles(7).unwrap();
+    assert_eq!(
+        emu.read_memory(0x400, 6).unwrap(),
+        [0x11, 0x22, 0x33, 0x33, 0x22, 0x11]
+    );
+    // I is left alone
+    assert_eq!(emu.get_i(), 0x403);
+
+    // I = data, load V4 - V6 forwards, then V9 - V7 backwards
+    let mut emu = emu_with(&[0xA300, 0x5463, 0x5973], &[0xA, 0xB, 0xC]);
+    emu.set_variant(Variant::XoChip);
+    emu.run_cycles(3).unwrap();
+    let regs: Vec<u8> = (4..10).map(|x| emu.get_register(reg(x))).collect();
+    assert_eq!(regs, [0xA, 0xB, 0xC, 0xC, 0xB, 0xA]);
+}
+
+#[test]
+fn run_cycles_stops_on_error() {
+    // V0 = 5, V0 += 1, an unknown opcode
+    let mut emu = emu_with(&[0x6005, 0x7001, 0xFFFF], &[]);
+    assert_eq!(
+        emu.run_cycles(10),
+        Err(ExecError::UnknownOpcode {
+            pc: 0x204,
+            op: 0xFFFF
+        })
+    );
+    assert_eq!(emu.cycle_count(), 2);
+    assert_eq!(emu.get_register(reg(0)), 6);
+
+    // RET with nothing on the stack
+    let mut emu = emu_with(&[0x00EE], &[]);
+    assert_eq!(
+        emu.run_cycles(1),
+        Err(ExecError::StackUnderflow { pc: 0x200 })
+    );
+}
+
+#[test]
+fn every_supported_opcode_decodes() {
+    // each table entry is what some opcode matching it decodes to
+    for (id, info) in supported_opcodes().iter().enumerate() {
+        let reached = (0..=0xFFFF)
+            .filter(|op| info.matches(*op))
+            .any(|op| decode(op).is_some_and(|instr| instr.id() == id));
+        assert!(reached, "{}", info.mnemonic);
+    }
+}
+
+#[test]
+fn decode_agrees_with_the_opcode_table() {
+    // an opcode decodes to the first table entry it matches, or not at all if none do
+    for op in 0..=0xFFFF {
+        let first = supported_opcodes().iter().position(|info| info.matches(op));
+        assert_eq!(decode(op).map(|instr| instr.id()), first, "0x{:04X}", op);
+    }
+}
+
+#[test]
+fn decoded_variant_matches_the_opcode_table() {
+    for op in 0..=0xFFFF {
+        if let Some(instr) = decode(op) {
+            assert_eq!(
+                supported_opcodes()[instr.id()].variant,
+                instr.variant(),
+                "0x{:04X}",
+                op
+            );
+        }
+    }
+}
+
+#[test]
+fn flag_wins_when_x_is_vf() {
+    // the flag is written after the result, so with X = F the result is lost
+    for (words, expected) in [
+        // VF = 0xFF, V1 = 1, VF += V1 (carries)
+        (&[0x6FFF, 0x6101, 0x8F14][..], 1),
+        // VF = 5, V1 = 3, VF -= V1 (no borrow)
+        (&[0x6F05, 0x6103, 0x8F15], 1),
+        // VF = 3, VF >>= 1 (bit shifted out is 1)
+        (&[0x6F03, 0x8F16], 1),
+        // VF = 4, VF >>= 1 (bit shifted out is 0)
+        (&[0x6F04, 0x8F16], 0),
+        // VF = 5, V1 = 3, VF = V1 - VF (borrows)
+        (&[0x6F05, 0x6103, 0x8F17], 0),
+        // VF = 0x80, VF <<= 1 (bit shifted out is 1)
+        (&[0x6F80, 0x8F1E], 1),
+    ] {
+        let mut emu = emu_with(words, &[]);
+        emu.run_cycles(words.len() as u64).unwrap();
+        assert_eq!(vf(&emu), expected, "0x{:04X}", words[words.len() - 1]);
+    }
+}
+
+#[test]
+fn setters_prepare_a_snippet() {
+    // V0 += V1, then store V0 at I
+    let mut emu = emu_with(&[0x8014, 0xF055], &[]);
+    emu.set_register(reg(0), 0x20);
+    emu.set_register(reg(1), 0x22);
+    emu.set_i(0x400);
+    assert_eq!(emu.get_i(), 0x400);
+
+    emu.run_cycles(2).unwrap();
+    assert_eq!(emu.read_memory(0x400, 1).unwrap(), [0x42]);
+}
+
+#[test]
+fn run_frame_reports_drawing_and_beeping() {
+    // ST = V0 = 5, DRAW, spin
+    let mut emu = emu_with(&[0x6005, 0xF018, 0xA300, 0xD001, 0x1208], &[0x80]);
+    assert_eq!(
+        emu.run_frame(2),
+        Ok(FrameOutcome {
+            screen_changed: false,
+            beeping: true
+        })
+    );
+    assert_eq!(
+        emu.run_frame(2),
+        Ok(FrameOutcome {
+            screen_changed: true,
+            beeping: true
+        })
+    );
+    // nothing new drawn; the sound timer runs out after 5 frames
+    assert!(!emu.run_frame(10).unwrap().screen_changed);
+    assert!(emu.run_frame(10).unwrap().beeping);
+    assert!(!emu.run_frame(10).unwrap().beeping);
+
+    // a reset clears the screen, which needs redrawing too
+    emu.reset();
+    assert!(emu.run_frame(0).unwrap().screen_changed);
+}
+
+#[test]
+fn quirk_presets() {
+    let quirks = |flags: [bool; 8]| Quirks {
+        vf_reset: flags[0],
+        increment_i: flags[1],
+        display_wait: flags[2],
+        clip_sprites: flags[3],
+        clip_counts_collision: flags[4],
+        hires_wraps_vertically: flags[5],
+        shift_uses_vy: flags[6],
+        jump_uses_vx: flags[7],
+    };
+    let (t, f) = (true, false);
+    assert_eq!(Quirks::cosmac_vip(), quirks([t, t, t, t, f, f, t, f]));
+    assert_eq!(Quirks::super_chip(), quirks([f, f, f, t, t, f, f, t]));
+    assert_eq!(Quirks::xo_chip(), quirks([f, t, f, f, f, f, t, f]));
+    assert_eq!(Quirks::modern(), quirks([f; 8]));
+    assert_eq!(Quirks::default(), Quirks::modern());
+
+    // picking a variant picks its preset, unless the quirks are given explicitly
+    let schip = EmuBuilder::new().variant(Variant::SuperChip);
+    assert_eq!(schip.build().quirks(), Quirks::super_chip());
+    let schip = EmuBuilder::new().variant(Variant::SuperChip);
+    let vip = schip.quirks(Quirks::cosmac_vip()).build();
+    assert_eq!(vip.quirks(), Quirks::cosmac_vip());
+}
+
+#[test]
+fn preset_quirks_behave() {
+    // VF = 5, V0 |= V1 (resets VF), V1 = 0x81, V2 = V1 >> 1 (from VY), I = 0x300, store V0 - V1
+    let words = [0x6F05, 0x8011, 0x6181, 0x8216, 0xA300, 0xF155];
+    let mut emu = EmuBuilder::new().quirks(Quirks::cosmac_vip()).build();
+    emu.load(&rom(&words, &[])).unwrap();
+    emu.run_cycles(6).unwrap();
+    assert_eq!(emu.get_register(reg(2)), 0x40);
+    assert_eq!(vf(&emu), 1);
+    assert_eq!(emu.get_i(), 0x302);
+
+    // a second DRAW waits for the next frame
+    let mut emu = EmuBuilder::new().quirks(Quirks::cosmac_vip()).build();
+    emu.load(&rom(&[0xD001, 0xD001, 0x1204], &[])).unwrap();
+    emu.run_cycles(5).unwrap();
+    assert_eq!(emu.get_pc(), 0x202);
+    emu.tick_timers();
+    emu.tick().unwrap();
+    assert_eq!(emu.get_pc(), 0x204);
+
+    // SUPER-CHIP's BXNN jumps to XNN + VX
+    let mut emu = EmuBuilder::new().variant(Variant::SuperChip).build();
+    emu.load(&rom(&[0x6302, 0xB310], &[])).unwrap();
+    emu.run_cycles(2).unwrap();
+    assert_eq!(emu.get_pc(), 0x312);
+}
+
+#[test]
+fn clock_hz_ticks_timers_at_60hz() {
+    // DT = V0 = 60, spin
+    let mut emu = Emu::new_with_clock_hz(600);
+    emu.load(&rom(&[0x603C, 0xF015, 0x1204], &[])).unwrap();
+
+    // one timer tick every 10 instructions
+    emu.run_cycles(9).unwrap();
+    assert_eq!(emu.delay_timer(), 60);
+    emu.run_cycles(1).unwrap();
+    assert_eq!(emu.delay_timer(), 59);
+    emu.run_cycles(50).unwrap();
+    assert_eq!(emu.delay_timer(), 54);
+    emu.run_cycles(600).unwrap();
+    assert_eq!(emu.delay_timer(), 0);
+}
+
+#[test]
+fn load_opcode_runs_one_instruction() {
+    let mut emu = Emu::new();
+    emu.set_register(reg(1), 200);
+    emu.set_register(reg(2), 100);
+
+    // V1 += V2, which carries
+    emu.load_opcode(0x8124);
+    assert_eq!(emu.get_pc(), 0x200);
+    emu.tick().unwrap();
+    assert_eq!(emu.get_register(reg(1)), 44);
+    assert_eq!(vf(&emu), 1);
+
+    // written at the new pc
+    emu.load_opcode(0x7105);
+    emu.tick().unwrap();
+    assert_eq!(emu.get_register(reg(1)), 49);
+    assert_eq!(emu.get_pc(), 0x204);
+
+    // split across the end and the start of RAM at 0xFFF (reached with JMP 0xFFF)
+    emu.load_opcode(0x1FFF);
+    emu.tick().unwrap();
+    let result = catch_unwind(AssertUnwindSafe(|| emu.load_opcode(0x7102)));
+    result.expect("no panic");
+    assert_eq!(emu.read_memory(0, 1).unwrap(), [0x02]);
+    emu.tick().unwrap();
+    assert_eq!(emu.get_register(reg(1)), 51);
+    assert_eq!(emu.get_pc(), 0x001);
+}
+
+#[test]
+fn silent_mode_leaves_the_timers_alone() {
+    // ST = DT = V0 = 0x40, spin
+    let words = [0x6040, 0xF018, 0xF015, 0x1206];
+    let mut loud = emu_with(&words, &[]);
+    let mut silent = emu_with(&words, &[]);
+    silent.set_silent(true);
+
+    for _ in 0..30 {
+        let outcome = loud.run_frame(7).unwrap();
+        assert!(outcome.beeping);
+        assert!(!silent.run_frame(7).unwrap().beeping);
+        assert_eq!(silent.sound_timer(), loud.sound_timer());
+        assert_eq!(silent.delay_timer(), loud.delay_timer());
+        assert_eq!(silent.state_hash(), loud.state_hash());
+    }
+    assert_eq!(silent.sound_timer(), 0x40 - 30);
+}
+
+#[test]
+fn bcd_wraps_at_the_end_of_ram() {
+    let mut emu = Emu::new();
+    emu.set_register(reg(0), 123);
+    emu.set_i(0xFFE);
+    emu.load_opcode(0xF033);
+    emu.tick().unwrap();
+    assert_eq!(emu.read_memory(0xFFE, 2).unwrap(), [1, 2]);
+    assert_eq!(emu.read_memory(0, 1).unwrap(), [3]);
+
+    // I past the end of RAM altogether
+    emu.set_i(0xFFFF);
+    emu.load_opcode(0xF033);
+    let result = catch_unwind(AssertUnwindSafe(|| emu.tick()));
+    assert!(result.expect("no panic").is_ok());
+}
+
+#[test]
+fn emulated_seconds_follow_the_clock() {
+    // spin
+    let mut emu = Emu::new_with_clock_hz(500);
+    emu.load(&rom(&[0x1200], &[])).unwrap();
+    emu.run_cycles(1500).unwrap();
+    assert_eq!(emu.emulated_seconds(), 3.0);
+    emu.reset();
+    assert_eq!(emu.emulated_seconds(), 0.0);
+
+    // no clock, no idea how long it took
+    let mut emu = emu_with(&[0x1200], &[]);
+    emu.run_cycles(1500).unwrap();
+    assert_eq!(emu.emulated_seconds(), 0.0);
+}
+
+#[test]
+fn configurable_stack_depth() {
+    // 20 nested CALLs, each to the next instruction, then spin
+    let mut words: Vec<u16> = (0..20).map(|i| 0x2202 + 2 * i).collect();
+    words.push(0x1228);
+    let program = rom(&words, &[]);
+
+    let mut emu = EmuBuilder::new().stack_size(24).build();
+    assert_eq!(emu.stack_size(), 24);
+    emu.load(&program).unwrap();
+    emu.run_cycles(25).unwrap();
+    assert_eq!(emu.get_pc(), 0x228);
+
+    // the 17th CALL overflows the default stack
+    let mut emu = EmuBuilder::new().build();
+    assert_eq!(emu.stack_size(), 16);
+    emu.load(&program).unwrap();
+    assert_eq!(
+        emu.run_cycles(25),
+        Err(ExecError::StackOverflow { pc: 0x220 })
+    );
+
+    let emu = EmuBuilder::new().stack_size(1000).build();
+    assert_eq!(emu.stack_size(), MAX_STACK_SIZE);
+}
+
+#[test]
+fn cost_budget_runs_fewer_draws() {
+    // V0 += 1 forever, or DRAW forever
+    let arithmetic = [0x7001, 0x1200];
+    let draws = [0xD001, 0x1200];
+    let cycles = |words: &[u16]| {
+        let mut emu = EmuBuilder::new()
+            .instruction_costs(weighted_costs())
+            .build();
+        emu.load(&rom(words, &[])).unwrap();
+        emu.run_frame(20).unwrap();
+        emu.cycle_count()
+    };
+    assert_eq!(cycles(&arithmetic), 20);
+    assert!(cycles(&draws) < 20);
+
+    // every instruction costs 1 by default
+    let mut emu = emu_with(&draws, &[]);
+    emu.run_frame(20).unwrap();
+    assert_eq!(emu.cycle_count(), 20);
+}
+
+#[test]
+fn rpl_flags_store_and_restore() {
+    // V0 - V2 = 1, 2, 3, save V0 - V2 to the flags, clear them, load back V0 - V1
+    let words = [
+        0x6001, 0x6102, 0x6203, 0xF275, 0x6000, 0x6100, 0x6200, 0xF185,
+    ];
+    let mut emu = EmuBuilder::new().variant(Variant::SuperChip).build();
+    emu.load(&rom(&words, &[])).unwrap();
+    emu.run_cycles(words.len() as u64).unwrap();
+    assert_eq!(emu.get_register(reg(0)), 1);
+    assert_eq!(emu.get_register(reg(1)), 2);
+    assert_eq!(emu.get_register(reg(2)), 0);
+    assert_eq!(emu.rpl_flags()[..4], [1, 2, 3, 0]);
+
+    // kept across reset, and a frontend can hand back saved ones
+    emu.reset();
+    assert_eq!(emu.rpl_flags()[2], 3);
+    let mut flags = [0; 16];
+    flags[0] = 9;
+    emu.set_rpl_flags(flags);
+    emu.load(&rom(&[0xF085], &[])).unwrap();
+    emu.tick().unwrap();
+    assert_eq!(emu.get_register(reg(0)), 9);
+
+    // plain CHIP-8 doesn't have them
+    let mut emu = emu_with(&[0xF075], &[]);
+    assert!(emu.tick().is_err());
+}
+
+#[test]
+fn protect_low_ram_blocks_fx55() {
+    // V0 = 7, I = 0x1FF, store V0 - V1
+    let words = [0x6007, 0xA1FF, 0xF155];
+    let mut emu = EmuBuilder::new().protect_low_ram(true).build();
+    emu.load(&rom(&words, &[])).unwrap();
+    emu.run_cycles(2).unwrap();
+    assert_eq!(
+        emu.tick(),
+        Err(ExecError::ProtectedWrite {
+            pc: 0x204,
+            addr: 0x1FF
+        })
+    );
+    assert_eq!(emu.read_memory(0x1FF, 1).unwrap(), [0]);
+
+    // off by default
+    let mut emu = emu_with(&words, &[]);
+    emu.run_cycles(3).unwrap();
+    assert_eq!(emu.read_memory(0x1FF, 2).unwrap(), [7, 0]);
+
+    // from 0x200 on is fine
+    let mut emu = EmuBuilder::new().protect_low_ram(true).build();
+    emu.load(&rom(&[0xA200, 0xF033], &[])).unwrap();
+    emu.run_cycles(2).unwrap();
+}
+
+#[test]
+fn reg_covers_v0_to_vf() {
+    assert_eq!(Reg::new(15), Some(Reg::VF));
+    assert_eq!(Reg::new(16), None);
+    assert_eq!(Reg::all().count(), 16);
+    assert!(Reg::all().map(Reg::index).eq(0..16));
+}
+
+#[test]
+fn shift_left_follows_the_vy_quirk() {
+    // V1 = 0x01, V2 = 0x80, V1 = V1 << 1 (or V2 << 1 with the quirk)
+    let words = [0x6101, 0x6280, 0x812E];
+    for (shift_uses_vy, v1, carry) in [(false, 0x02, 0), (true, 0x00, 1)] {
+        let quirks = Quirks {
+            shift_uses_vy,
+            ..Quirks::modern()
+        };
+        let mut emu = EmuBuilder::new().quirks(quirks).build();
+        emu.load(&rom(&words, &[])).unwrap();
+        emu.run_cycles(3).unwrap();
+        assert_eq!(emu.get_register(reg(1)), v1);
+        assert_eq!(vf(&emu), carry);
+        assert_eq!(emu.get_register(reg(2)), 0x80);
+    }
+}
@@ -0,0 +1,369 @@
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+#[cfg(feature = "alloc")]
+use std::{cell::RefCell, rc::Rc};
+
+#[cfg(feature = "alloc")]
+use chip8_core::DebugEvent;
+use chip8_core::{decode, Emu, EmuBuilder, ExecError, OutOfBounds, StopReason, RECENT_OPS};
+use common::*;
+
+// install an event hook that keeps every event it's given
+#[cfg(feature = "alloc")]
+fn record_events(emu: &mut Emu) -> Rc<RefCell<Vec<DebugEvent>>> {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = events.clone();
+    emu.set_event_hook(Box::new(move |event| sink.borrow_mut().push(event)));
+    events
+}
+
+#[test]
+fn skip_current_resumes_after_unknown_opcode() {
+    // V0 = 1, an unknown opcode, V1 = 2
+    let mut emu = emu_with(&[0x6001, 0xFFFF, 0x6102], &[]);
+    emu.tick().unwrap();
+    assert!(matches!(
+        emu.tick(),
+        Err(ExecError::UnknownOpcode { pc: 0x202, .. })
+    ));
+    assert_eq!(emu.get_pc(), 0x202);
+
+    emu.skip_current();
+    emu.tick().unwrap();
+    assert_eq!(emu.get_pc(), 0x206);
+    assert_eq!(emu.get_register(reg(1)), 2);
+}
+
+#[test]
+fn skip_current_wraps_at_the_end_of_ram() {
+    let mut emu = emu_with(&[], &[]);
+    emu.set_pc(0xFFE).unwrap();
+    emu.skip_current();
+    assert_eq!(emu.get_pc(), 0x000);
+}
+
+#[test]
+fn set_pc_rejects_addresses_past_the_end() {
+    let mut emu = Emu::new();
+    assert_eq!(emu.set_pc(0xFFE), Ok(()));
+    assert_eq!(emu.get_pc(), 0xFFE);
+
+    // a whole opcode has to fit
+    assert_eq!(
+        emu.set_pc(0xFFF),
+        Err(OutOfBounds {
+            index: 0xFFF,
+            len: 0xFFF
+        })
+    );
+    assert!(emu.set_pc(0x1000).is_err());
+    assert_eq!(emu.get_pc(), 0xFFE);
+}
+
+#[test]
+fn read_memory_rejects_ranges_past_the_end() {
+    let emu = Emu::new();
+    assert_eq!(emu.read_memory(0xFFE, 2).unwrap().len(), 2);
+    assert_eq!(
+        emu.read_memory(0xFFF, 2),
+        Err(OutOfBounds {
+            index: 0x1001,
+            len: 0x1000
+        })
+    );
+    // start + len would overflow
+    assert_eq!(
+        emu.read_memory(0x200, usize::MAX),
+        Err(OutOfBounds {
+            index: usize::MAX,
+            len: 0x1000
+        })
+    );
+}
+
+#[test]
+fn step_over_runs_the_whole_call() {
+    // CALL 0x208, V1 = 1, spin; the subroutine at 0x208 sets V0 = 5, adds 1 and returns
+    let mut emu = emu_with(
+        &[0x2208, 0x6101, 0x1204, 0x0000, 0x6005, 0x7001, 0x00EE],
+        &[],
+    );
+    emu.step_over().unwrap();
+    assert_eq!(emu.get_pc(), 0x202);
+    assert_eq!(emu.cycle_count(), 4);
+    assert_eq!(emu.get_register(reg(0)), 6);
+
+    // anything else is a single step
+    emu.step_over().unwrap();
+    assert_eq!(emu.get_pc(), 0x204);
+    assert_eq!(emu.get_register(reg(1)), 1);
+}
+
+#[test]
+fn peek_and_step_over_wrap_at_the_end_of_ram() {
+    // jump to 0xFFF, whose opcode ends with the first font byte (0xF0) at 0x000
+    let mut emu = emu_with(&[0x1FFF], &[]);
+    emu.tick().unwrap();
+    let result = catch_unwind(AssertUnwindSafe(|| emu.peek_op()));
+    assert_eq!(result.expect("no panic"), 0x00F0);
+
+    // CALL 0x300 in the last two bytes returns to 0x000
+    let mut emu = emu_with(&[], &[0x00, 0xEE]);
+    emu.poke_opcode(0xFFE, 0x2300).unwrap();
+    emu.set_pc(0xFFE).unwrap();
+    emu.step_over().unwrap();
+    assert_eq!(emu.get_pc(), 0x000);
+    assert_eq!(emu.cycle_count(), 2);
+}
+
+#[test]
+fn watchpoint_catches_fx55() {
+    // V0 = 0x11, I = 0x210, store V0 - V2 (0x210 - 0x212), spin
+    let mut emu = emu_with(&[0x6011, 0xA210, 0xF255, 0x1206], &[]);
+    assert!(emu.watch_range(0x211, 0x220));
+    assert_eq!(emu.run(10), Ok(StopReason::Watchpoint(0x211)));
+    // stopped just after the store
+    assert_eq!(emu.get_pc(), 0x206);
+    assert_eq!(emu.run(10), Ok(StopReason::CycleLimit));
+
+    assert!(emu.unwatch_range(0x211, 0x220));
+    assert!(!emu.unwatch_range(0x211, 0x220));
+    assert!(!emu.watch_range(5, 5));
+}
+
+#[cfg(feature = "debug_checks")]
+#[test]
+fn add_overflow_event() {
+    let mut emu = Emu::new();
+    let events = record_events(&mut emu);
+    emu.set_register(reg(3), 250);
+
+    // V3 += 5 just reaches 255
+    emu.load_opcode(0x7305);
+    emu.tick().unwrap();
+    assert!(events.borrow().is_empty());
+
+    // V3 += 1 wraps
+    emu.load_opcode(0x7301);
+    emu.tick().unwrap();
+    assert_eq!(
+        *events.borrow(),
+        [DebugEvent::AddOverflow {
+            pc: 0x202,
+            x: 3,
+            value: 255,
+            nn: 1
+        }]
+    );
+    assert_eq!(emu.get_register(reg(3)), 0);
+}
+
+#[test]
+fn list_and_clear_breakpoints_and_watchpoints() {
+    let mut emu = Emu::new();
+    for addr in [0x200, 0x204, 0x208] {
+        assert!(emu.add_breakpoint(addr));
+    }
+    // adding one twice doesn't list it twice
+    assert!(emu.add_breakpoint(0x204));
+    assert!(emu.remove_breakpoint(0x200));
+    let mut breakpoints = emu.breakpoints().to_vec();
+    breakpoints.sort();
+    assert_eq!(breakpoints, [0x204, 0x208]);
+
+    emu.clear_breakpoints();
+    assert!(emu.breakpoints().is_empty());
+    assert!(!emu.has_breakpoint(0x204));
+    // the table is usable again afterwards
+    assert!(emu.add_breakpoint(0x20A));
+
+    assert!(emu.watch_range(0x300, 0x310));
+    assert!(emu.watch_range(0x400, 0x401));
+    assert_eq!(emu.watchpoints().len(), 2);
+    emu.clear_watchpoints();
+    assert!(emu.watchpoints().is_empty());
+}
+
+#[test]
+fn run_stops_at_breakpoints() {
+    // CALL 0x208, V1 = 1, spin; the subroutine at 0x208 sets V0 = 5, adds 1 and returns
+    let mut emu = emu_with(
+        &[0x2208, 0x6101, 0x1204, 0x0000, 0x6005, 0x7001, 0x00EE],
+        &[],
+    );
+    assert!(emu.add_breakpoint(0x20A));
+    assert_eq!(emu.run(100), Ok(StopReason::Breakpoint(0x20A)));
+    assert_eq!(emu.get_register(reg(0)), 5);
+    // continuing runs past it
+    assert_eq!(emu.run(100), Ok(StopReason::CycleLimit));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn collision_event_only_on_overlap() {
+    // I = sprite, a pixel at (0, 0), one at (8, 0), then (0, 0) again
+    let mut emu = emu_with(
+        &[
+            0xA300, 0x6000, 0x6100, 0xD011, 0x6008, 0xD011, 0x6000, 0xD011,
+        ],
+        &[0x80],
+    );
+    let events = record_events(&mut emu);
+    emu.run_cycles(6).unwrap();
+    assert!(events.borrow().is_empty());
+
+    emu.run_cycles(2).unwrap();
+    assert_eq!(
+        *events.borrow(),
+        [DebugEvent::Collision {
+            pc: 0x20E,
+            x: 0,
+            y: 0,
+            vf: 1
+        }]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn misaligned_pc_event() {
+    // JMP 0x205, then zeroes (NOPs) from there on
+    let mut emu = emu_with(&[0x1205], &[]);
+    let events = record_events(&mut emu);
+    emu.run_cycles(2).unwrap();
+    assert!(events.borrow().is_empty());
+
+    emu.set_warn_on_misaligned_pc(true);
+    emu.tick().unwrap();
+    assert_eq!(*events.borrow(), [DebugEvent::MisalignedPc { pc: 0x207 }]);
+}
+
+#[test]
+fn frozen_register_ignores_loads() {
+    // V3 = 0x11, V3 += 1, V4 = 0x22, V3 = 0x33
+    let mut emu = emu_with(&[0x6311, 0x7301, 0x6422, 0x6333], &[]);
+    assert!(emu.freeze_register(3, 0x42));
+    assert!(!emu.freeze_register(16, 0));
+    emu.run_cycles(3).unwrap();
+    assert_eq!(emu.get_register(reg(3)), 0x42);
+    assert_eq!(emu.get_register(reg(4)), 0x22);
+
+    assert!(emu.unfreeze_register(3));
+    assert!(!emu.unfreeze_register(3));
+    emu.tick().unwrap();
+    assert_eq!(emu.get_register(reg(3)), 0x33);
+}
+
+#[test]
+fn histogram_counts_each_instruction() {
+    // V0 = 5, V0 += 1 twice
+    let mut emu = EmuBuilder::new().histogram(true).build();
+    emu.load(&rom(&[0x6005, 0x7001, 0x7001], &[])).unwrap();
+    emu.run_cycles(3).unwrap();
+    let counts = emu.opcode_histogram().unwrap();
+    assert_eq!(counts[decode(0x6005).unwrap().id()], 1);
+    assert_eq!(counts[decode(0x7001).unwrap().id()], 2);
+    assert_eq!(counts.iter().sum::<u64>(), 3);
+
+    assert!(Emu::new().opcode_histogram().is_none());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn coverage_lists_executed_patterns() {
+    // V0 = 5, V0 += 1, I = 0x300, spin
+    let mut emu = EmuBuilder::new().histogram(true).build();
+    emu.load(&rom(&[0x6005, 0x7001, 0xA300, 0x1206], &[]))
+        .unwrap();
+    emu.run_cycles(6).unwrap();
+    assert_eq!(emu.coverage(), [0x1000, 0x6000, 0x7000, 0xA000]);
+
+    emu.reset_histogram();
+    assert!(emu.coverage().is_empty());
+    assert!(Emu::new().coverage().is_empty());
+}
+
+#[test]
+fn poked_jump_runs_next() {
+    // V0 = 1, V1 = 2
+    let mut emu = emu_with(&[0x6001, 0x6102], &[]);
+    emu.tick().unwrap();
+    emu.poke_opcode(0x202, 0x1300).unwrap();
+    assert_eq!(emu.read_memory(0x202, 2).unwrap(), [0x13, 0x00]);
+    emu.tick().unwrap();
+    assert_eq!(emu.get_pc(), 0x300);
+    assert_eq!(emu.get_register(reg(1)), 0);
+
+    // the whole opcode has to fit in RAM
+    assert!(emu.poke_opcode(0xFFE, 0x1200).is_ok());
+    assert!(emu.poke_opcode(0xFFF, 0x1200).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn stack_events_follow_call_and_ret() {
+    // CALL 0x206, spin; the subroutine at 0x206 just returns
+    let mut emu = emu_with(&[0x2206, 0x1202, 0x0000, 0x00EE], &[]);
+    let events = record_events(&mut emu);
+    // off by default
+    emu.run_cycles(3).unwrap();
+    assert!(events.borrow().is_empty());
+
+    emu.reset();
+    emu.load(&rom(&[0x2206, 0x1202, 0x0000, 0x00EE], &[]))
+        .unwrap();
+    emu.set_trace_stack(true);
+    emu.run_cycles(3).unwrap();
+    assert_eq!(
+        *events.borrow(),
+        [
+            DebugEvent::StackPush {
+                pc: 0x200,
+                addr: 0x202,
+                sp: 1
+            },
+            DebugEvent::StackPop {
+                pc: 0x206,
+                addr: 0x202,
+                sp: 0
+            },
+        ]
+    );
+}
+
+#[test]
+fn last_opcode_is_the_one_just_run() {
+    // V1 = 3, draw 5 rows at (V1, V2)
+    let mut emu = emu_with(&[0x6103, 0xD125], &[]);
+    assert_eq!(emu.last_opcode(), None);
+    emu.run_cycles(2).unwrap();
+    assert_eq!(emu.last_opcode(), Some(0xD125));
+    #[cfg(feature = "alloc")]
+    assert_eq!(emu.last_disasm().as_deref(), Some("DRAW V1 V2 5"));
+
+    emu.reset();
+    assert_eq!(emu.last_opcode(), None);
+    #[cfg(feature = "alloc")]
+    assert_eq!(emu.last_disasm(), None);
+}
+
+#[test]
+fn recent_opcodes_keep_the_last_few() {
+    // V0 += 1 twenty times, then spin
+    let mut words = vec![0x7001; 20];
+    words.push(0x1228);
+    let mut emu = emu_with(&words, &[]);
+    emu.run_cycles(2).unwrap();
+    assert!(emu.recent_opcodes().eq([(0x200, 0x7001), (0x202, 0x7001)]));
+
+    // only the newest `RECENT_OPS` are kept, oldest first
+    emu.run_cycles(20).unwrap();
+    let recent: Vec<_> = emu.recent_opcodes().collect();
+    assert_eq!(recent.len(), RECENT_OPS);
+    assert_eq!(recent[0], (0x20C, 0x7001));
+    assert_eq!(recent[RECENT_OPS - 1], (0x228, 0x1228));
+
+    emu.reset();
+    assert_eq!(emu.recent_opcodes().count(), 0);
+}
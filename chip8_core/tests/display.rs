@@ -0,0 +1,396 @@
+mod common;
+
+use chip8_core::{
+    opcode_touches_display, DrawInfo, DrawMode, Emu, EmuBuilder, ExecError, Quirks, SizeMismatch,
+    Variant,
+};
+use common::*;
+
+// three rows, drawn at V0 = 0, V1 = 62 so the last row hangs off the bottom of a hires screen
+const SPRITE: [u8; 3] = [0xFF, 0x81, 0xFF];
+
+#[test]
+fn hires_collision_counts_rows() {
+    // HIRES, V0 = 0, V1 = 62, I = sprite, DRAW twice
+    let mut emu = Emu::new();
+    emu.set_variant(Variant::SuperChip);
+    emu.load(&rom(
+        &[0x00FF, 0x6000, 0x613E, 0xA300, 0xD013, 0xD013],
+        &SPRITE,
+    ))
+    .unwrap();
+    emu.run_cycles(5).unwrap();
+    // nothing to hit yet, but the row clipped off the bottom counts
+    assert_eq!(vf(&emu), 1);
+    emu.tick().unwrap();
+    // both rows on screen collide, plus the clipped one
+    assert_eq!(vf(&emu), 3);
+}
+
+#[test]
+fn lores_collision_is_a_flag() {
+    // V0 = V1 = 0 so the whole sprite is on screen
+    let words = [0x6000, 0x6100, 0xA300, 0xD013, 0xD013];
+    for variant in [Variant::Chip8, Variant::SuperChip] {
+        let mut emu = Emu::new();
+        emu.set_variant(variant);
+        emu.load(&rom(&words, &SPRITE)).unwrap();
+        emu.run_cycles(4).unwrap();
+        assert_eq!(vf(&emu), 0, "{:?}", variant);
+        emu.tick().unwrap();
+        assert_eq!(vf(&emu), 1, "{:?}", variant);
+    }
+}
+
+#[test]
+fn sprite_reads_wrap_at_the_end_of_ram() {
+    // I = 0xFFE, V0 = 0, 15 row DRAW: the last 2 bytes of RAM, then the fontset from 0x000
+    let mut emu = emu_with(&[0xAFFE, 0x6000, 0xD00F], &[]);
+    emu.run_cycles(3).unwrap();
+    let row = |emu: &Emu, y| (0..8).map(|x| emu.pixel(x, y).unwrap()).collect::<Vec<_>>();
+    assert_eq!(row(&emu, 0), [false; 8]);
+    assert_eq!(row(&emu, 1), [false; 8]);
+    // the top of the "0" glyph, 0xF0
+    assert_eq!(
+        row(&emu, 2),
+        [true, true, true, true, false, false, false, false]
+    );
+
+    // a 16x16 SUPER-CHIP sprite starting on the very last byte
+    let mut emu = emu_with(&[0xAFFF, 0xD000], &[]);
+    emu.set_variant(Variant::SuperChip);
+    emu.run_cycles(2).unwrap();
+}
+
+#[test]
+fn big_sprites_need_super_chip() {
+    let mut emu = emu_with(&[0xD000], &[]);
+    assert_eq!(
+        emu.tick(),
+        Err(ExecError::UnknownOpcode {
+            pc: 0x200,
+            op: 0xD000
+        })
+    );
+}
+
+#[test]
+fn draw_modes_on_overlapping_sprites() {
+    // V0 = 0, draw 0xF0 then 0x3C over it at (0, 0)
+    let words = [0x6000, 0xA300, 0xD001, 0xA301, 0xD001];
+    for (mode, row, collided) in [
+        (DrawMode::Xor, [1, 1, 0, 0, 1, 1, 0, 0], 1),
+        (DrawMode::Set, [1, 1, 1, 1, 1, 1, 0, 0], 0),
+        (DrawMode::Clear, [0; 8], 0),
+    ] {
+        let mut emu = emu_with(&words, &[0xF0, 0x3C]);
+        emu.set_draw_mode(mode);
+        emu.run_cycles(5).unwrap();
+        let lit: Vec<u8> = (0..8).map(|x| emu.pixel(x, 0).unwrap() as u8).collect();
+        assert_eq!(lit, row, "{:?}", mode);
+        assert_eq!(vf(&emu), collided, "{:?}", mode);
+    }
+}
+
+#[test]
+fn render_into_a_caller_buffer() {
+    // draw 0xA0 (two pixels with a gap) at (0, 0)
+    let mut emu = emu_with(&[0xA300, 0xD001], &[0xA0]);
+    emu.run_cycles(2).unwrap();
+
+    let (fg, bg) = (0xFFFF_FFFF, 0xFF00_0000);
+    let mut buf = vec![0; 64 * 32];
+    emu.render_into(&mut buf, fg, bg).unwrap();
+    assert_eq!(buf[..4], [fg, bg, fg, bg]);
+    assert!(buf[4..].iter().all(|pixel| *pixel == bg));
+
+    assert_eq!(
+        emu.render_into(&mut buf[..10], fg, bg),
+        Err(SizeMismatch {
+            expected: 64 * 32,
+            actual: 10
+        })
+    );
+}
+
+#[test]
+fn last_draw_matches_the_registers() {
+    // VA = 7, VB = 9, I = sprite, 3 row DRAW at (VA, VB)
+    let mut emu = emu_with(&[0x6A07, 0x6B09, 0xA300, 0xDAB3], &[]);
+    assert_eq!(emu.last_draw(), None);
+    emu.run_cycles(4).unwrap();
+    assert_eq!(
+        emu.last_draw(),
+        Some(DrawInfo {
+            x: 7,
+            y: 9,
+            width: 8,
+            height: 3,
+            addr: SPRITE_ADDR
+        })
+    );
+
+    emu.reset();
+    assert_eq!(emu.last_draw(), None);
+}
+
+#[test]
+fn pixel_accessors_check_bounds() {
+    let mut emu = Emu::new();
+    assert_eq!(emu.pixel(0, 0), Some(false));
+    assert_eq!(emu.pixel(63, 31), Some(false));
+    assert_eq!(emu.pixel(64, 0), None);
+    assert_eq!(emu.pixel(0, 32), None);
+    assert_eq!(emu.pixel(usize::MAX, usize::MAX), None);
+
+    // returns whether the pixel was already lit
+    assert!(!emu.set_pixel(63, 31, true));
+    assert!(emu.set_pixel(63, 31, true));
+    assert_eq!(emu.pixel(63, 31), Some(true));
+    assert!(emu.get_display()[31 * 64 + 63]);
+
+    // off the display: ignored
+    assert!(!emu.set_pixel(64, 31, true));
+    assert_eq!(emu.get_display().iter().filter(|pixel| **pixel).count(), 1);
+
+    // hires has more room
+    emu.set_variant(Variant::SuperChip);
+    emu.load_opcode(0x00FF);
+    emu.tick().unwrap();
+    assert_eq!(emu.pixel(127, 63), Some(false));
+    assert_eq!(emu.pixel(128, 0), None);
+}
+
+#[test]
+fn tall_sprites_light_every_row() {
+    // V0 = 20, sprite height V0, I = sprite, DRAW (N = 1) at (0, 0) twice
+    let words = [0x6014, 0xF01D, 0xA300, 0xD111, 0xD111];
+    let sprite = [0x80; 20];
+
+    // FX1D is an extension
+    let mut emu = emu_with(&words, &sprite);
+    assert!(emu.run_cycles(2).is_err());
+
+    let mut emu = emu_with(&words, &sprite);
+    emu.set_tall_sprites(true);
+    emu.run_cycles(4).unwrap();
+    for y in 0..32 {
+        assert_eq!(emu.pixel(0, y), Some(y < 20), "row {}", y);
+    }
+    assert_eq!(emu.last_draw().unwrap().height, 20);
+
+    // the height only lasts for one DRAW
+    emu.run_cycles(1).unwrap();
+    assert_eq!(emu.pixel(0, 0), Some(false));
+    assert_eq!(emu.pixel(0, 1), Some(true));
+    assert_eq!(vf(&emu), 1);
+}
+
+#[test]
+fn tick_outcome_flags_draws() {
+    // V0 = 0, I = sprite, DRAW, V0 += 1, DRAW, spin
+    let words = [0x6000, 0xA300, 0xD005, 0x7001, 0xD005, 0x1208];
+    let mut emu = emu_with(&words, &[0xFF; 5]);
+    let drew: Vec<bool> = (0..6).map(|_| emu.tick().unwrap().drew).collect();
+    assert_eq!(drew, [false, false, true, false, true, false]);
+
+    // with `display_wait` the frame ends at the first DRAW
+    let mut emu = emu_with(&words, &[0xFF; 5]);
+    emu.set_quirks(Quirks::cosmac_vip());
+    emu.run_frame(10).unwrap();
+    assert_eq!(emu.cycle_count(), 3);
+    assert!(!emu.tick().unwrap().drew);
+    // run_frame ticked the timers, so this is a new frame
+    assert!(emu.tick().unwrap().drew);
+
+    // a DRAW held back until the next frame doesn't count
+    emu.set_pc(0x204).unwrap();
+    assert!(!emu.tick().unwrap().drew);
+    assert_eq!(emu.get_pc(), 0x204);
+    emu.tick_timers();
+    assert!(emu.tick().unwrap().drew);
+}
+
+#[test]
+fn opcodes_that_touch_the_display() {
+    // CLS, DRAW, SCROLL RIGHT
+    for op in [0x00E0, 0xD125, 0x00FB] {
+        assert!(opcode_touches_display(op), "0x{:04X}", op);
+    }
+    // V0 = 0x12, an unknown opcode
+    for op in [0x6012, 0xFFFF] {
+        assert!(!opcode_touches_display(op), "0x{:04X}", op);
+    }
+}
+
+#[test]
+fn clipped_pixels_and_collisions() {
+    for counts in [false, true] {
+        let quirks = Quirks {
+            clip_sprites: true,
+            clip_counts_collision: counts,
+            ..Quirks::modern()
+        };
+        // I = sprite, a row at (60, 0) with 4 pixels off the right edge, VE = VF,
+        // then 3 rows at (0, 30) with one off the bottom, VD = VF
+        let words = [
+            0xA300, 0x603C, 0x6100, 0xD011, 0x8EF0, 0x6000, 0x611E, 0xD013, 0x8DF0,
+        ];
+        let mut emu = EmuBuilder::new().quirks(quirks).build();
+        emu.load(&rom(&words, &[0xFF, 0x01, 0x01])).unwrap();
+        emu.run_cycles(9).unwrap();
+        assert_eq!(emu.get_register(reg(0xE)), counts as u8);
+        assert_eq!(emu.get_register(reg(0xD)), counts as u8);
+
+        // wrapping sprites ignore the setting
+        let quirks = Quirks {
+            clip_sprites: false,
+            ..quirks
+        };
+        let mut emu = EmuBuilder::new().quirks(quirks).build();
+        emu.load(&rom(&words[..4], &[0xFF])).unwrap();
+        emu.run_cycles(4).unwrap();
+        assert_eq!(vf(&emu), 0);
+    }
+}
+
+#[test]
+fn draw_origin_wraps_onto_the_screen() {
+    for clip_sprites in [false, true] {
+        let quirks = Quirks {
+            clip_sprites,
+            ..Quirks::modern()
+        };
+        // I = sprite, one pixel at (70, 33), which is (6, 1) on a 64x32 screen
+        let mut emu = EmuBuilder::new().quirks(quirks).build();
+        emu.load(&rom(&[0xA300, 0x6046, 0x6121, 0xD011], &[0x80]))
+            .unwrap();
+        emu.run_cycles(4).unwrap();
+        assert_eq!(emu.pixel(6, 1), Some(true));
+        assert_eq!(emu.get_display().iter().filter(|pixel| **pixel).count(), 1);
+        let draw = emu.last_draw().unwrap();
+        assert_eq!((draw.x, draw.y), (6, 1));
+    }
+}
+
+#[test]
+fn planes_composite_into_indices() {
+    // both planes, I = sprite, a 1 row DRAW: the first row goes to plane 1, the next to plane 2
+    let words = [0xF301, 0xA300, 0x6000, 0xD001, 0x1208];
+    let mut emu = EmuBuilder::new().variant(Variant::XoChip).build();
+    emu.load(&rom(&words, &[0xF0, 0x0F])).unwrap();
+    emu.run_cycles(4).unwrap();
+    assert_eq!(emu.plane_bits(0), 1);
+    assert_eq!(emu.plane_bits(4), 2);
+    assert!(emu.get_plane(1).unwrap()[7]);
+    assert!(emu.get_plane(2).is_none());
+
+    // FN01 is XO-CHIP only
+    let mut emu = EmuBuilder::new().variant(Variant::SuperChip).build();
+    emu.load(&rom(&words, &[])).unwrap();
+    assert!(emu.tick().is_err());
+}
+
+#[test]
+fn four_planes_give_sixteen_colors() {
+    // one pixel in plane 3 at (0, 0), planes 1 + 4 at (8, 0), all of them at (16, 0)
+    let words = [
+        0xA300, 0xF401, 0xD011, 0x6008, 0xF901, 0xD011, 0x6010, 0xFF01, 0xD011,
+    ];
+    let sprite = [0x80; 4];
+    let mut emu = EmuBuilder::new()
+        .variant(Variant::XoChip)
+        .plane_count(4)
+        .build();
+    assert_eq!(emu.plane_count(), 4);
+    emu.load(&rom(&words, &sprite)).unwrap();
+    emu.run_cycles(9).unwrap();
+    let indices: Vec<u8> = emu.display_indices().collect();
+    assert_eq!((indices[0], indices[8], indices[16]), (4, 9, 15));
+    assert_eq!(indices[1], 0);
+    assert!(emu.get_plane(3).unwrap()[16]);
+
+    // with the usual 2 planes the extra mask bits are ignored
+    let mut emu = EmuBuilder::new().variant(Variant::XoChip).build();
+    emu.load(&rom(&words, &sprite)).unwrap();
+    emu.run_cycles(9).unwrap();
+    let indices: Vec<u8> = emu.display_indices().collect();
+    assert_eq!((indices[0], indices[8], indices[16]), (0, 1, 3));
+    assert!(emu.get_plane(2).is_none());
+}
+
+#[test]
+fn bottom_edge_clips_or_wraps() {
+    // a 2 row sprite on the bottom row: whether the second row reaches the top
+    let wraps = |clip_sprites, hires_wraps_vertically, hires| {
+        let quirks = Quirks {
+            clip_sprites,
+            hires_wraps_vertically,
+            ..Quirks::modern()
+        };
+        let mut emu = EmuBuilder::new()
+            .variant(Variant::SuperChip)
+            .quirks(quirks)
+            .build();
+        let bottom = if hires { 63 } else { 31 };
+        // (HIRES,) V1 = bottom, I = sprite, DRAW at (0, V1)
+        let mut words = vec![0x6100 | bottom, 0xA300, 0xD012];
+        if hires {
+            words.insert(0, 0x00FF);
+        }
+        emu.load(&rom(&words, &[0x80, 0x80])).unwrap();
+        emu.run_cycles(words.len() as u64).unwrap();
+        assert_eq!(emu.pixel(0, bottom as usize), Some(true));
+        emu.pixel(0, 0).unwrap()
+    };
+    for hires in [false, true] {
+        assert!(wraps(false, false, hires));
+        assert!(!wraps(true, false, hires));
+    }
+    // the quirk only lets hires wrap
+    assert!(!wraps(true, true, false));
+    assert!(wraps(true, true, true));
+}
+
+#[test]
+fn run_until_draw_stops_on_the_draw() {
+    // V0 = 5, V1 = 6, I = sprite, DRAW, spin
+    let mut emu = emu_with(&[0x6005, 0x6106, 0xA300, 0xD011, 0x1208], &[0x80]);
+    assert_eq!(emu.run_until_draw(100), Ok(true));
+    assert_eq!(emu.cycle_count(), 4);
+    assert_eq!(emu.get_pc(), 0x208);
+
+    // nothing but jumps left, so it stops at the cap
+    assert_eq!(emu.run_until_draw(10), Ok(false));
+    assert_eq!(emu.cycle_count(), 14);
+
+    // CLS counts too
+    let mut emu = emu_with(&[0x6001, 0x00E0], &[]);
+    assert_eq!(emu.run_until_draw(10), Ok(true));
+    assert_eq!(emu.cycle_count(), 2);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn screen_copy_round_trip() {
+    let mut emu = Emu::new();
+    let pattern: Vec<bool> = (0..64 * 32).map(|i| i % 3 == 0).collect();
+    emu.set_screen(&pattern).unwrap();
+    let copy = emu.get_screen_copy();
+    assert_eq!(copy, pattern);
+
+    emu.set_screen(&[false; 64 * 32]).unwrap();
+    assert!(emu.get_display().iter().all(|pixel| !pixel));
+    emu.set_screen(&copy).unwrap();
+    assert_eq!(emu.get_display(), pattern);
+
+    // has to match the current resolution
+    assert_eq!(
+        emu.set_screen(&[true; 10]),
+        Err(SizeMismatch {
+            expected: 64 * 32,
+            actual: 10
+        })
+    );
+    assert_eq!(emu.get_display(), pattern);
+}
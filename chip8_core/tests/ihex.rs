@@ -0,0 +1,39 @@
+#![cfg(feature = "ihex")]
+
+mod common;
+
+use chip8_core::{Emu, LoadError};
+use common::*;
+
+#[test]
+fn load_hex_records() {
+    // V0 = 5, V0 += 1 at 0x200, a data byte at 0x300, end of file
+    let good = ":040200006005700124\n:01030000AB51\n:00000001FF\n";
+    let mut emu = Emu::new();
+    emu.load_hex(good).unwrap();
+    assert_eq!(emu.read_memory(SPRITE_ADDR, 1).unwrap(), [0xAB]);
+    emu.run_cycles(2).unwrap();
+    assert_eq!(emu.get_register(reg(0)), 6);
+
+    // the second record's checksum is off by one; nothing is written
+    let bad = ":040200006005700124\n:01030000AB52\n";
+    let mut emu = Emu::new();
+    assert_eq!(emu.load_hex(bad), Err(LoadError::MalformedHex { line: 2 }));
+    assert_eq!(emu.peek_op(), 0);
+
+    assert_eq!(
+        emu.load_hex("not hex"),
+        Err(LoadError::MalformedHex { line: 1 })
+    );
+    // nothing but the end of file record
+    assert_eq!(emu.load_hex(":00000001FF\n"), Err(LoadError::Empty));
+    assert_eq!(emu.load_hex(""), Err(LoadError::Empty));
+    // two bytes from 0xFFF run off the end of RAM
+    assert_eq!(
+        emu.load_hex(":020FFF00AABB8B\n"),
+        Err(LoadError::HexOutOfRange {
+            line: 1,
+            addr: 0x1000
+        })
+    );
+}
@@ -0,0 +1,141 @@
+mod common;
+
+#[cfg(feature = "replay")]
+use chip8_core::replay_and_hash;
+use chip8_core::{Emu, InputEvent, KeyPriority};
+use common::*;
+
+#[test]
+fn keypress_rejects_out_of_range_keys() {
+    let mut emu = Emu::new();
+    assert!(emu.keypress(15, true));
+    assert!(!emu.keypress(16, true));
+    assert!(!emu.keypress(usize::MAX, true));
+    assert_eq!(emu.key_mask(), 0x8000);
+    assert!(!emu.is_key_pressed(16));
+}
+
+#[test]
+fn one_frame_tap_is_latched() {
+    // wait for a key into V0, spin
+    let mut emu = emu_with(&[0xF00A, 0x1202], &[]);
+    emu.run_frame(10).unwrap();
+
+    // pressed and released between two frames
+    emu.keypress(5, true);
+    emu.keypress(5, false);
+    assert!(emu.is_key_pressed(5));
+    emu.run_frame(10).unwrap();
+    assert!(!emu.is_key_pressed(5));
+    assert_eq!(emu.get_register(reg(0)), 5);
+    assert_eq!(emu.get_pc(), 0x202);
+
+    // a release after an instruction has seen the press applies straight away
+    emu.keypress(2, true);
+    emu.tick().unwrap();
+    emu.keypress(2, false);
+    assert!(!emu.is_key_pressed(2));
+}
+
+#[cfg(feature = "replay")]
+#[test]
+fn one_frame_tap_in_a_replay() {
+    let program = rom(&[0xF00A, 0x1202], &[]);
+    let event = |cycle, pressed| InputEvent {
+        cycle,
+        key: 7,
+        pressed,
+    };
+    // a press and release before the same instruction counts like a one instruction hold
+    let tap = [event(3, true), event(3, false)];
+    let hold = [event(3, true), event(4, false)];
+    assert_ne!(
+        replay_and_hash(&program, &tap, 20, 1),
+        replay_and_hash(&program, &[], 20, 1)
+    );
+    assert_eq!(
+        replay_and_hash(&program, &tap, 20, 1),
+        replay_and_hash(&program, &hold, 20, 1)
+    );
+}
+
+#[test]
+fn wait_key_tie_break() {
+    // wait for a key into V0 with 3 then 5 held
+    for (priority, expected) in [(KeyPriority::LowestIndex, 3), (KeyPriority::MostRecent, 5)] {
+        let mut emu = emu_with(&[0xF00A], &[]);
+        emu.set_key_priority(priority);
+        emu.keypress(3, true);
+        emu.keypress(5, true);
+        emu.tick().unwrap();
+        assert_eq!(emu.get_register(reg(0)), expected, "{:?}", priority);
+    }
+    assert_eq!(Emu::new().key_priority(), KeyPriority::LowestIndex);
+
+    // most recent is by press order, and pressing a held key again doesn't count
+    let mut emu = emu_with(&[0xF00A], &[]);
+    emu.set_key_priority(KeyPriority::MostRecent);
+    emu.keypress(5, true);
+    emu.keypress(3, true);
+    emu.keypress(5, true);
+    emu.tick().unwrap();
+    assert_eq!(emu.get_register(reg(0)), 3);
+}
+
+#[test]
+fn queued_tap_reaches_fx0a() {
+    // wait for a key into V0, spin
+    let mut emu = emu_with(&[0xF00A, 0x1202], &[]);
+    emu.tick().unwrap();
+    assert!(emu.queue_key_event(7, true));
+    assert!(emu.queue_key_event(7, false));
+    assert!(!emu.queue_key_event(16, true));
+    assert_eq!(
+        emu.queued_key_events(),
+        [
+            InputEvent {
+                cycle: 1,
+                key: 7,
+                pressed: true
+            },
+            InputEvent {
+                cycle: 1,
+                key: 7,
+                pressed: false
+            },
+        ]
+    );
+
+    emu.run_frame(10).unwrap();
+    assert_eq!(emu.get_register(reg(0)), 7);
+    assert!(!emu.is_key_pressed(7));
+    assert!(emu.queued_key_events().is_empty());
+}
+
+#[test]
+fn queued_taps_keep_their_order() {
+    // wait for a key into V0, then into V1, spin
+    let mut emu = emu_with(&[0xF00A, 0xF10A, 0x1204], &[]);
+    for key in [3, 5] {
+        emu.queue_key_event(key, true);
+        emu.queue_key_event(key, false);
+    }
+    emu.run_frame(10).unwrap();
+    assert_eq!(emu.get_register(reg(0)), 3);
+    assert_eq!(emu.get_register(reg(1)), 5);
+}
+
+#[test]
+fn pressed_keys_lists_held_keys_in_order() {
+    // spin
+    let mut emu = emu_with(&[0x1200], &[]);
+    assert_eq!(emu.pressed_keys().count(), 0);
+    emu.keypress(10, true);
+    emu.keypress(2, true);
+    assert_eq!(emu.pressed_keys().collect::<Vec<_>>(), [2, 10]);
+
+    // once an instruction has seen the press, a release applies straight away
+    emu.tick().unwrap();
+    emu.keypress(10, false);
+    assert_eq!(emu.pressed_keys().collect::<Vec<_>>(), [2]);
+}
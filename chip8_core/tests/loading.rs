@@ -0,0 +1,76 @@
+use chip8_core::{detect_variant, rom_checksum, Emu, LoadError, Variant};
+
+#[cfg(feature = "alloc")]
+#[test]
+fn hexdump_round_trip() {
+    let mut emu = Emu::new();
+    emu.load(b"Hello, CHIP-8!\x00\xff\x10 more bytes here")
+        .unwrap();
+    // starts 4 bytes before the program, not on a 16 byte boundary
+    let dump = emu.ram_hexdump(0x1FC, 40);
+    assert!(dump.starts_with(
+        "01FC  00 00 00 00 48 65 6C 6C 6F 2C 20 43 48 49 50 2D  |....Hello, CHIP-|\n"
+    ));
+    assert_eq!(dump.lines().count(), 3);
+
+    let mut copy = Emu::new();
+    copy.load_hexdump(&dump).unwrap();
+    assert_eq!(
+        copy.read_memory(0x1FC, 40).unwrap(),
+        emu.read_memory(0x1FC, 40).unwrap()
+    );
+
+    // dumps stop at the end of RAM
+    assert_eq!(emu.ram_hexdump(0xFF8, 100).lines().count(), 1);
+    assert_eq!(
+        copy.load_hexdump("0FFF  00 01"),
+        Err(LoadError::MalformedDump { line: 1 })
+    );
+    assert_eq!(
+        copy.load_hexdump("\n0200  0G"),
+        Err(LoadError::MalformedDump { line: 2 })
+    );
+}
+
+#[test]
+fn empty_rom_is_an_error() {
+    let mut emu = Emu::new();
+    assert_eq!(emu.load(&[]), Err(LoadError::Empty));
+    assert_eq!(LoadError::Empty.to_string(), "ROM is empty");
+}
+
+#[test]
+fn rom_checksum_is_stable_and_distinct() {
+    let a = [0x60, 0x05, 0x12, 0x00];
+    let b = [0x60, 0x06, 0x12, 0x00];
+    assert_eq!(rom_checksum(&a), rom_checksum(&a));
+    assert_ne!(rom_checksum(&a), rom_checksum(&b));
+    // pinned so cache keys stay valid between versions
+    assert_eq!(rom_checksum(&[]), 0xCBF2_9CE4_8422_2325);
+}
+
+#[test]
+fn detect_variant_from_opcodes() {
+    let bytes = |words: &[u16]| {
+        words
+            .iter()
+            .flat_map(|w| w.to_be_bytes())
+            .collect::<Vec<u8>>()
+    };
+    // CLS, V0 = 5, draw 5 rows, spin
+    assert_eq!(
+        detect_variant(&bytes(&[0x00E0, 0x6005, 0xD125, 0x1200])),
+        Variant::Chip8
+    );
+    assert_eq!(detect_variant(&[]), Variant::Chip8);
+
+    for op in [0x00FF, 0x00C4, 0xD120, 0xF330, 0xF475] {
+        assert_eq!(detect_variant(&bytes(&[op, 0x1200])), Variant::SuperChip);
+    }
+    // XO-CHIP wins over SUPER-CHIP wherever it turns up
+    for op in [0xF201, 0xF000, 0xF002, 0x5122, 0x5123] {
+        assert_eq!(detect_variant(&bytes(&[0x00FF, op])), Variant::XoChip);
+    }
+    // words are only read at even offsets
+    assert_eq!(detect_variant(&[0x12, 0x00, 0xFF, 0x00]), Variant::Chip8);
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use chip8_core::{Emu, Variant};
+use common::*;
+
+fn rewindable(words: &[u16], sprite: &[u8]) -> Emu {
+    let mut emu = emu_with(words, sprite);
+    emu.set_micro_rewind(true);
+    emu
+}
+
+#[test]
+fn undo_arithmetic() {
+    // V0 = 5, V1 = 0xFF, V0 += V1 (carries)
+    let mut emu = rewindable(&[0x6005, 0x61FF, 0x8014], &[]);
+    emu.run_cycles(3).unwrap();
+    assert_eq!(emu.get_register(reg(0)), 4);
+    assert_eq!(vf(&emu), 1);
+
+    assert!(emu.undo_instruction());
+    assert_eq!(emu.get_register(reg(0)), 5);
+    assert_eq!(vf(&emu), 0);
+    assert_eq!(emu.get_pc(), 0x204);
+    assert_eq!(emu.cycle_count(), 2);
+}
+
+#[test]
+fn undo_draw() {
+    // I = sprite, DRAW, DRAW again (erases it and sets VF)
+    let mut emu = rewindable(&[0xA300, 0xD001, 0xD001], &[0xAA]);
+    emu.run_cycles(2).unwrap();
+    let drawn = emu.get_screen_copy();
+    emu.tick().unwrap();
+    assert!(emu.get_display().iter().all(|pixel| !pixel));
+    assert_eq!(vf(&emu), 1);
+
+    assert!(emu.undo_instruction());
+    assert_eq!(emu.get_display(), &drawn[..]);
+    assert_eq!(vf(&emu), 0);
+    assert!(emu.undo_instruction());
+    assert!(emu.get_display().iter().all(|pixel| !pixel));
+}
+
+#[test]
+fn undo_every_instruction_back_to_the_start() {
+    let words = [
+        0x6005, 0x61FF, 0x8014, 0xA300, 0xD001, 0xD001, 0x00E0, 0xF055,
+    ];
+    let mut emu = rewindable(&words, &[0xAA]);
+    let mut hashes = vec![emu.state_hash()];
+    for _ in 0..words.len() {
+        emu.tick().unwrap();
+        hashes.push(emu.state_hash());
+    }
+
+    for hash in hashes.iter().rev().skip(1) {
+        assert!(emu.undo_instruction());
+        assert_eq!(emu.state_hash(), *hash);
+    }
+    assert!(!emu.undo_instruction());
+    assert_eq!(emu.get_pc(), 0x200);
+}
+
+#[test]
+fn undo_keeps_automatic_timers_in_step() {
+    // V0 = 10, DT = V0, then NOPs; at 180 Hz the timers tick every third instruction
+    let words = [0x600A, 0xF015, 0x0000, 0x0000, 0x0000];
+    let mut straight = rewindable(&words, &[]);
+    let mut rewound = rewindable(&words, &[]);
+    straight.set_clock_hz(180);
+    rewound.set_clock_hz(180);
+
+    // both one instruction in, one of them by running two and undoing the second
+    straight.tick().unwrap();
+    rewound.run_cycles(2).unwrap();
+    assert!(rewound.undo_instruction());
+    for _ in 0..4 {
+        straight.tick().unwrap();
+        rewound.tick().unwrap();
+        assert_eq!(straight.delay_timer(), rewound.delay_timer());
+    }
+    assert_eq!(straight.state_hash(), rewound.state_hash());
+}
+
+#[test]
+fn undo_replays_the_rng_override() {
+    // V0 = rand & 0xFF, V1 = rand & 0x0F, V2 = rand & 0xF0, V3 = rand & 0x81
+    let mut emu = rewindable(&[0xC0FF, 0xC10F, 0xC2F0, 0xC381], &[]);
+    emu.set_rng_override(vec![0xFF, 0x5A, 0x0F]);
+    emu.run_cycles(4).unwrap();
+    // NN masks each override value; the 4th CXNN starts over at the beginning of the list
+    let regs = |emu: &Emu| (0..4).map(|x| emu.get_register(reg(x))).collect::<Vec<_>>();
+    assert_eq!(regs(&emu), [0xFF, 0x0A, 0x00, 0x81]);
+
+    // undoing and running again gets the same values, not the next ones in the list
+    let hash = emu.state_hash();
+    for _ in 0..2 {
+        assert!(emu.undo_instruction());
+    }
+    emu.run_cycles(2).unwrap();
+    assert_eq!(regs(&emu), [0xFF, 0x0A, 0x00, 0x81]);
+    assert_eq!(emu.state_hash(), hash);
+}
+
+#[test]
+fn undo_register_range_stores() {
+    // V1 = 0x11, V2 = 0x22, I = 0x400, store V1 - V2, then V2 - V1 over the top (reverse order)
+    let mut emu = rewindable(&[0x6111, 0x6222, 0xA400, 0x5122, 0x5212], &[]);
+    emu.set_variant(Variant::XoChip);
+    emu.run_cycles(4).unwrap();
+    assert_eq!(emu.read_memory(0x400, 2).unwrap(), [0x11, 0x22]);
+    emu.tick().unwrap();
+    assert_eq!(emu.read_memory(0x400, 2).unwrap(), [0x22, 0x11]);
+
+    assert!(emu.undo_instruction());
+    assert_eq!(emu.read_memory(0x400, 2).unwrap(), [0x11, 0x22]);
+    assert!(emu.undo_instruction());
+    assert_eq!(emu.read_memory(0x400, 2).unwrap(), [0, 0]);
+}
+
+#[test]
+fn undo_range_load() {
+    // I = data, load V3 - V1 (reverse order)
+    let mut emu = rewindable(&[0xA300, 0x5313], &[1, 2, 3]);
+    emu.set_variant(Variant::XoChip);
+    emu.run_cycles(2).unwrap();
+    assert_eq!(emu.get_register(reg(3)), 1);
+    assert_eq!(emu.get_register(reg(1)), 3);
+
+    assert!(emu.undo_instruction());
+    assert!((1..4).all(|x| emu.get_register(reg(x)) == 0));
+    assert_eq!(emu.read_memory(SPRITE_ADDR, 3).unwrap(), [1, 2, 3]);
+}
+
+#[test]
+fn undo_wrapped_bcd() {
+    let mut emu = rewindable(&[], &[]);
+    let font = emu.read_memory(0, 1).unwrap()[0];
+    emu.set_register(reg(0), 123);
+    emu.set_i(0xFFE);
+    emu.load_opcode(0xF033);
+    emu.tick().unwrap();
+    assert_eq!(emu.read_memory(0, 1).unwrap(), [3]);
+
+    assert!(emu.undo_instruction());
+    assert_eq!(emu.read_memory(0, 1).unwrap(), [font]);
+    assert_eq!(emu.read_memory(0xFFE, 2).unwrap(), [0, 0]);
+}
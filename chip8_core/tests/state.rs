@@ -0,0 +1,212 @@
+mod common;
+
+use chip8_core::{Emu, EmuBuilder, ResetOptions};
+#[cfg(feature = "serde")]
+use chip8_core::{EmuState, MAX_SPRITE_HEIGHT};
+#[cfg(feature = "alloc")]
+use chip8_core::{StateDiff, Variant, MAX_DIFFS_PER_AREA};
+use common::*;
+
+#[test]
+fn randomized_ram_follows_the_seed() {
+    let a = EmuBuilder::new().rng_seed(7).randomize_ram(true).build();
+    let b = EmuBuilder::new().rng_seed(7).randomize_ram(true).build();
+    let c = EmuBuilder::new().rng_seed(8).randomize_ram(true).build();
+    let ram = a.read_memory(0, 4096).unwrap();
+    assert_eq!(ram, b.read_memory(0, 4096).unwrap());
+    assert_ne!(ram, c.read_memory(0, 4096).unwrap());
+
+    // the fontset survives and RAM is only randomized when asked for
+    assert_eq!(&ram[..80], a.fontset());
+    let zeroed = EmuBuilder::new().rng_seed(7).build();
+    assert!(zeroed
+        .read_memory(0x200, 0xE00)
+        .unwrap()
+        .iter()
+        .all(|b| *b == 0));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn unseeded_randomized_ram_differs_between_runs() {
+    let a = EmuBuilder::new().randomize_ram(true).build();
+    let b = EmuBuilder::new().randomize_ram(true).build();
+    assert_ne!(
+        a.read_memory(0x200, 0xE00).unwrap(),
+        b.read_memory(0x200, 0xE00).unwrap()
+    );
+}
+
+#[test]
+fn state_hash_changes_on_tick() {
+    let mut a = emu_with(&[0x6005, 0x7001], &[]);
+    let mut b = emu_with(&[0x6005, 0x7001], &[]);
+    assert_eq!(a.state_hash(), b.state_hash());
+
+    a.tick().unwrap();
+    assert_ne!(a.state_hash(), b.state_hash());
+    b.tick().unwrap();
+    assert_eq!(a.state_hash(), b.state_hash());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn diff_names_what_differs() {
+    // V3 = 5 or 7, CLS
+    let mut a = emu_with(&[0x6305, 0x00E0], &[]);
+    let mut b = emu_with(&[0x6307, 0x00E0], &[]);
+    assert_eq!(
+        a.diff(&b),
+        [StateDiff::RamByte {
+            addr: 0x201,
+            a: 5,
+            b: 7
+        }]
+    );
+
+    a.tick().unwrap();
+    b.tick().unwrap();
+    b.set_pixel(3, 4, true);
+    let diffs = a.diff(&b);
+    assert_eq!(
+        diffs[0],
+        StateDiff::Register {
+            index: 3,
+            a: 5,
+            b: 7
+        }
+    );
+    assert_eq!(diffs[0].to_string(), "V3 differs: 0x05 vs 0x07");
+    assert_eq!(
+        diffs.last(),
+        Some(&StateDiff::Pixel {
+            plane: 0,
+            x: 3,
+            y: 4,
+            a: false,
+            b: true
+        })
+    );
+
+    // a long run of differing RAM is cut short
+    let mut full = Emu::new();
+    full.load(&[0xFF; 100]).unwrap();
+    assert_eq!(Emu::new().diff(&full).len(), MAX_DIFFS_PER_AREA);
+    assert!(full.diff(&full).is_empty());
+}
+
+#[test]
+fn reset_with_keeps_what_it_is_told_to() {
+    // I = font 0, draw it at (V0, V1), V0 = random
+    let setup = || {
+        let mut emu = emu_with(&[0xA000, 0xD015, 0xC0FF], &[]);
+        emu.run_cycles(2).unwrap();
+        emu
+    };
+    let lit = |emu: &Emu| emu.get_display().iter().any(|pixel| *pixel);
+
+    let mut emu = setup();
+    emu.reset_with(ResetOptions::default());
+    assert!(!lit(&emu));
+    assert_eq!(emu.peek_op(), 0);
+    assert_eq!(emu.get_pc(), 0x200);
+
+    let mut emu = setup();
+    emu.reset_with(ResetOptions {
+        clear_screen: false,
+        ..Default::default()
+    });
+    assert!(lit(&emu));
+    assert_eq!(emu.peek_op(), 0);
+
+    let mut emu = setup();
+    emu.reset_with(ResetOptions {
+        wipe_ram: false,
+        ..Default::default()
+    });
+    assert!(!lit(&emu));
+    assert_eq!(emu.peek_op(), 0xA000);
+    assert_eq!(emu.fontset()[0], 0xF0);
+
+    // the same seed gives the same CXNN result, however far the generator had got
+    let opts = ResetOptions {
+        clear_screen: false,
+        wipe_ram: false,
+        reseed_rng: Some(7),
+    };
+    let mut a = setup();
+    let mut b = setup();
+    a.tick().unwrap();
+    a.reset_with(opts);
+    b.reset_with(opts);
+    a.run_cycles(3).unwrap();
+    b.run_cycles(3).unwrap();
+    assert_eq!(a.get_register(reg(0)), b.get_register(reg(0)));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn restore_round_trips_at_the_end_of_ram() {
+    // JMP 0xFFF, where the next opcode wraps around to 0x000
+    let mut emu = emu_with(&[0x1FFF], &[]);
+    emu.tick().unwrap();
+    let state = emu.snapshot();
+
+    let mut other = emu_with(&[], &[]);
+    assert!(other.restore(&state));
+    assert_eq!(other.get_pc(), 0xFFF);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn restore_refuses_planes_this_emulator_lacks() {
+    // select all four planes of a 4 plane XO-CHIP fork
+    let mut emu = emu_with(&[0xFF01], &[]);
+    emu.set_variant(Variant::XoChip);
+    emu.set_plane_count(4);
+    emu.tick().unwrap();
+    let state = emu.snapshot();
+
+    let mut other = emu_with(&[0x6001], &[]);
+    other.set_variant(Variant::XoChip);
+    let before = other.state_hash();
+    assert!(!other.restore(&state));
+    assert_eq!(other.state_hash(), before);
+
+    other.set_plane_count(4);
+    assert!(other.restore(&state));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn restore_refuses_a_corrupt_state() {
+    let emu = emu_with(&[0x6001], &[]);
+    let bytes = bincode::serialize(&emu.snapshot()).unwrap();
+    let corrupt = |edit: fn(&mut Vec<u8>)| {
+        let mut bytes = bytes.clone();
+        edit(&mut bytes);
+        bincode::deserialize::<EmuState>(&bytes).unwrap()
+    };
+
+    let states = [
+        // pc is the first field; 0x1000 is past the end of RAM
+        corrupt(|bytes| bytes[..2].copy_from_slice(&0x1000u16.to_le_bytes())),
+        // `sprite_height` is the last one, saved as `None` (a single 0): Some(0) and a row too many
+        corrupt(|bytes| {
+            bytes.pop();
+            bytes.extend([1, 0]);
+        }),
+        corrupt(|bytes| {
+            bytes.pop();
+            bytes.extend([1, MAX_SPRITE_HEIGHT + 1]);
+        }),
+    ];
+
+    let mut other = emu_with(&[], &[]);
+    let before = other.state_hash();
+    for state in &states {
+        assert!(!other.restore(state), "{:?}", state);
+        assert_eq!(other.state_hash(), before);
+    }
+    assert!(other.restore(&emu.snapshot()));
+}
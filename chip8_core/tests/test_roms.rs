@@ -0,0 +1,51 @@
+#![cfg(feature = "test_roms")]
+
+mod common;
+
+use chip8_core::test_roms::{CARRY_FLAGS, DELAY_COUNTER, PLUS_SIGN};
+use chip8_core::{Emu, Quirks};
+use common::*;
+
+#[test]
+fn plus_sign_draws_a_plus() {
+    let mut emu = Emu::new();
+    emu.load(PLUS_SIGN).unwrap();
+    emu.run_cycles(10).unwrap();
+    for x in 0..64 {
+        for y in 0..32 {
+            let lit = (y == 14 && (28..33).contains(&x)) || (x == 30 && (12..17).contains(&y));
+            assert_eq!(emu.pixel(x, y), Some(lit), "({}, {})", x, y);
+        }
+    }
+    assert_eq!(vf(&emu), 0);
+}
+
+#[test]
+fn delay_counter_counts_seconds() {
+    let mut emu = Emu::new();
+    emu.load(DELAY_COUNTER).unwrap();
+    for _ in 0..60 * 3 + 5 {
+        emu.run_frame(10).unwrap();
+    }
+    assert_eq!(emu.get_register(reg(1)), 3);
+}
+
+#[test]
+fn carry_flags_under_every_preset() {
+    for quirks in [
+        Quirks::modern(),
+        Quirks::cosmac_vip(),
+        Quirks::super_chip(),
+        Quirks::xo_chip(),
+    ] {
+        let mut emu = Emu::new();
+        emu.set_quirks(quirks);
+        emu.load(CARRY_FLAGS).unwrap();
+        emu.run_cycles(40).unwrap();
+        let flags: Vec<u8> = [0x0, 0xA, 0xB, 0xC, 0xD, 0xE]
+            .into_iter()
+            .map(|x| emu.get_register(reg(x)))
+            .collect();
+        assert_eq!(flags, [1, 1, 1, 0, 1, 1], "{:?}", quirks);
+    }
+}
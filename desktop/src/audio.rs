@@ -0,0 +1,202 @@
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::Sdl;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+
+// pitch of the beep; CHIP-8 only has the one tone so any audible frequency works
+const BEEP_HZ: f32 = 440.0;
+// kept well below full scale since the square wave is harsh
+const VOLUME: f32 = 0.2;
+const SAMPLE_RATE: i32 = 44_100;
+// samples in one 60 Hz frame (44100 divides evenly)
+const FRAME_SAMPLES: usize = SAMPLE_RATE as usize / 60;
+// the canonical 44 byte header; the two sizes in it are filled in by `WavRecorder::finish`
+const WAV_HEADER_LEN: u32 = 44;
+
+// shape of the beep (`--waveform`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Waveform {
+    // the classic buzzer sound
+    #[default]
+    Square,
+    // the softest; just the pure tone
+    Sine,
+    // somewhere in between
+    Triangle,
+}
+
+impl Waveform {
+    // the wave at `phase` (0 to 1 through one period), from -1 to 1
+    pub fn sample(self, phase: f32) -> f32 {
+        match self {
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Sine => (phase * std::f32::consts::TAU).sin(),
+            // rises from -1 to 1 over the first half, then falls back
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        }
+    }
+}
+
+// plays the beep while the sound timer is running
+pub struct Beeper {
+    device: AudioDevice<Tone>,
+}
+
+impl Beeper {
+    pub fn new(sdl: &Sdl, waveform: Waveform) -> Result<Self, String> {
+        let audio = sdl.audio()?;
+        let desired = AudioSpecDesired {
+            freq: Some(SAMPLE_RATE),
+            channels: Some(1),
+            samples: None,
+        };
+
+        let device = audio.open_playback(None, &desired, |spec| Tone::new(waveform, spec.freq))?;
+
+        Ok(Self { device })
+    }
+
+    // start or stop output; cheap to call every frame
+    pub fn set_playing(&self, playing: bool) {
+        if playing {
+            self.device.resume();
+        } else {
+            self.device.pause();
+        }
+    }
+}
+
+// captures the beep to a 16 bit mono WAV file, one frame at a time
+// it's generated from the sound timer rather than tapped off the audio device, so it records even with
+// --mute / --silent and stays in step with the game however the device buffers
+pub struct WavRecorder {
+    out: BufWriter<File>,
+    wave: Tone,
+    samples: u32,
+}
+
+impl WavRecorder {
+    pub fn new(path: &str, waveform: Waveform) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        write_wav_header(&mut out, 0)?;
+        Ok(Self {
+            out,
+            wave: Tone::new(waveform, SAMPLE_RATE),
+            samples: 0,
+        })
+    }
+
+    // append one 60 Hz frame: the beep while `beeping`, silence otherwise
+    pub fn push_frame(&mut self, beeping: bool) -> io::Result<()> {
+        for _ in 0..FRAME_SAMPLES {
+            let sample = if beeping {
+                self.wave.next_sample()
+            } else {
+                0.0
+            };
+            self.out
+                .write_all(&((sample * i16::MAX as f32) as i16).to_le_bytes())?;
+        }
+        self.samples += FRAME_SAMPLES as u32;
+        Ok(())
+    }
+
+    // fill in the sizes now that the length is known and flush everything to disk
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.seek(SeekFrom::Start(0))?;
+        write_wav_header(&mut self.out, self.samples * 2)?;
+        self.out.flush()
+    }
+}
+
+// RIFF / WAVE header for `data_len` bytes of 16 bit mono PCM at `SAMPLE_RATE`
+fn write_wav_header(out: &mut impl Write, data_len: u32) -> io::Result<()> {
+    let rate = SAMPLE_RATE as u32;
+    out.write_all(b"RIFF")?;
+    out.write_all(&(WAV_HEADER_LEN - 8 + data_len).to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    // format chunk: 16 bytes, PCM, 1 channel, sample rate, byte rate, block align, bits per sample
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&rate.to_le_bytes())?;
+    out.write_all(&(rate * 2).to_le_bytes())?;
+    out.write_all(&2u16.to_le_bytes())?;
+    out.write_all(&16u16.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())
+}
+
+// the beep's samples, one after another
+struct Tone {
+    waveform: Waveform,
+    // fraction of a period advanced per sample
+    phase_inc: f32,
+    phase: f32,
+}
+
+impl Tone {
+    fn new(waveform: Waveform, sample_rate: i32) -> Self {
+        Self {
+            waveform,
+            phase_inc: BEEP_HZ / sample_rate as f32,
+            phase: 0.0,
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let sample = self.waveform.sample(self.phase) * VOLUME;
+        self.phase = (self.phase + self.phase_inc) % 1.0;
+        sample
+    }
+}
+
+impl AudioCallback for Tone {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        for sample in out.iter_mut() {
+            *sample = self.next_sample();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAVEFORMS: [Waveform; 3] = [Waveform::Square, Waveform::Sine, Waveform::Triangle];
+
+    #[test]
+    fn waveforms_stay_in_range() {
+        for waveform in WAVEFORMS {
+            let samples: Vec<f32> = (0..100)
+                .map(|i| waveform.sample(i as f32 / 100.0))
+                .collect();
+            assert!(
+                samples.iter().all(|s| (-1.0..=1.0).contains(s)),
+                "{waveform:?}"
+            );
+            // each one swings all the way from one end to the other
+            assert!(samples.iter().any(|&s| s > 0.99), "{waveform:?}");
+            assert!(samples.iter().any(|&s| s < -0.99), "{waveform:?}");
+        }
+    }
+
+    #[test]
+    fn tone_is_scaled_to_the_volume() {
+        for waveform in WAVEFORMS {
+            let mut tone = Tone::new(waveform, SAMPLE_RATE);
+            for _ in 0..FRAME_SAMPLES {
+                assert!(tone.next_sample().abs() <= VOLUME, "{waveform:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use chip8_core::{decode, Emu, ExecError};
+
+use crate::debug_server::registers;
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// write a report of `err` for attaching to bug reports: `chip8-crash-<timestamp>.txt` with the ROM's
+// checksum, the final registers and the last few instructions, plus the whole machine next to it in
+// `chip8-crash-<timestamp>.state` (the same format as the save slots)
+// returns the report's path
+pub fn write_crash_report(
+    dir: &str,
+    emu: &Emu,
+    rom: &str,
+    checksum: u64,
+    err: &ExecError,
+) -> io::Result<String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let name = format!("chip8-crash-{}", timestamp);
+    fs::create_dir_all(dir)?;
+
+    let state = bincode::serialize(&emu.snapshot()).map_err(io::Error::other)?;
+    let state_path = Path::new(dir).join(format!("{}.state", name));
+    fs::write(&state_path, state)?;
+
+    // writing into a String can't fail
+    let mut text = String::new();
+    writeln!(text, "error: {}", err).unwrap();
+    writeln!(text, "rom: {} (checksum {:016x})", rom, checksum).unwrap();
+    writeln!(text, "variant: {:?}", emu.variant()).unwrap();
+    writeln!(text, "quirks: {:?}", emu.quirks()).unwrap();
+    writeln!(text, "cycles: {}", emu.cycle_count()).unwrap();
+    writeln!(text, "state: {}", registers(emu)).unwrap();
+    writeln!(text, "snapshot: {}", state_path.display()).unwrap();
+    writeln!(text, "last instructions, oldest first:").unwrap();
+    for (addr, op) in emu.recent_opcodes() {
+        match decode(op) {
+            Some(instr) => writeln!(text, "  {:04X}  {:04X}  {}", addr, op, instr).unwrap(),
+            None => writeln!(text, "  {:04X}  {:04X}  ???", addr, op).unwrap(),
+        }
+    }
+
+    let path = Path::new(dir).join(format!("{}.txt", name));
+    fs::write(&path, text)?;
+    Ok(path.display().to_string())
+}
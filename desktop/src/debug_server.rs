@@ -0,0 +1,319 @@
+use chip8_core::{Emu, ExecError, FrameOutcome, Reg, StopReason};
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+// a tiny line based debug protocol over TCP, for driving the emulator from external tools (`--debug-server PORT`)
+//
+// one client at a time connects to 127.0.0.1:PORT and sends one command per line
+// every command gets exactly one reply line, starting with `ok` or `err`
+// numbers are hex (a leading `0x` is optional) and replies print them as hex too
+//
+//  command          reply
+//  reg              ok pc=0200 i=0000 dt=00 st=00 v0=00 v1=00 ... vf=00
+//  mem ADDR LEN     ok 00 E0 A2 2A ...  (LEN bytes starting at ADDR)
+//  break ADDR       ok                  (stop before running the instruction at ADDR)
+//  delete ADDR      ok                  (remove the breakpoint at ADDR)
+//  poke ADDR OP     ok                  (overwrite the instruction at ADDR with the opcode OP)
+//  step             ok pc=0202          (run a single instruction)
+//  skip             ok pc=0202          (step over the instruction without running it)
+//  continue         ok stopped REASON PC
+//  pause            ok stopped pause PC
+//
+// the emulator starts paused so breakpoints can be set before the game runs
+// `continue` isn't answered until emulation stops again: REASON is `breakpoint`, `watchpoint ADDR`,
+// or `pause` (another client command arrived, e.g. `pause`, which is answered by the same reply)
+// execution errors stop emulation and are reported as `err ...` instead; after an unknown opcode,
+// `skip` then `continue` carries on past it
+
+// listens for a debugger and applies its commands between frames
+pub struct DebugServer {
+    messages: Receiver<Message>,
+    client: Option<TcpStream>,
+    // the port actually listened on (the OS picks one when asked for port 0)
+    port: u16,
+    // running between a `continue` and the next stop
+    running: bool,
+}
+
+// from the listener thread to the main loop
+enum Message {
+    Connected(TcpStream),
+    Line(String),
+}
+
+impl DebugServer {
+    // start listening; connections are accepted in the background
+    pub fn new(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let port = listener.local_addr()?.port();
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || listen(listener, sender));
+
+        Ok(Self {
+            messages,
+            client: None,
+            port,
+            running: false,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    // replaces `Emu::run_frame`: handles any waiting commands, then runs the frame only if a `continue` is active
+    pub fn run_frame(&mut self, emu: &mut Emu, ticks: usize) -> FrameOutcome {
+        while let Ok(message) = self.messages.try_recv() {
+            match message {
+                Message::Connected(stream) => {
+                    self.client = Some(stream);
+                    self.running = false;
+                }
+                Message::Line(line) => {
+                    // anything sent while running interrupts the `continue`
+                    if self.running {
+                        self.running = false;
+                        self.reply(format!("ok stopped pause {:04X}", emu.get_pc()));
+                        if line.trim() == "pause" {
+                            continue;
+                        }
+                    }
+                    let reply = self.command(emu, &line);
+                    if let Some(reply) = reply {
+                        self.reply(reply);
+                    }
+                }
+            }
+        }
+
+        if !self.running {
+            return FrameOutcome::default();
+        }
+
+        match emu.run(ticks as u64) {
+            Ok(StopReason::CycleLimit) => (),
+            Ok(StopReason::Breakpoint(pc)) => {
+                self.stop(format!("ok stopped breakpoint {:04X}", pc))
+            }
+            Ok(StopReason::Watchpoint(addr)) => self.stop(format!(
+                "ok stopped watchpoint {:04X} {:04X}",
+                addr,
+                emu.get_pc()
+            )),
+            Err(err) => self.stop(format!("err {}", err)),
+        }
+
+        // `run` leaves the timers alone; the desktop sets `clock_hz` so they tick along with the instructions
+        if emu.clock_hz() == 0 {
+            emu.tick_timers();
+        }
+
+        FrameOutcome {
+            screen_changed: true,
+            beeping: emu.sound_timer() > 0 && !emu.is_silent(),
+        }
+    }
+
+    fn stop(&mut self, reply: String) {
+        self.running = false;
+        self.reply(reply);
+    }
+
+    // `None` when the reply is deferred until emulation stops (`continue`)
+    fn command(&mut self, emu: &mut Emu, line: &str) -> Option<String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let reply = match words.as_slice() {
+            ["reg"] => Ok(registers(emu)),
+            ["mem", addr, len] => parse(addr).and_then(|addr| {
+                let len = parse(len)?;
+                let bytes = emu
+                    .read_memory(addr, len as usize)
+                    .map_err(|err| err.to_string())?;
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+                Ok(hex.join(" "))
+            }),
+            ["break", addr] => parse(addr).and_then(|addr| {
+                if emu.add_breakpoint(addr) {
+                    Ok(String::new())
+                } else {
+                    Err(String::from("too many breakpoints"))
+                }
+            }),
+            ["delete", addr] => parse(addr).and_then(|addr| {
+                if emu.remove_breakpoint(addr) {
+                    Ok(String::new())
+                } else {
+                    Err(format!("no breakpoint at {:04X}", addr))
+                }
+            }),
+            ["poke", addr, op] => parse(addr).and_then(|addr| {
+                let op = parse(op)?;
+                emu.poke_opcode(addr, op)
+                    .map(|_| String::new())
+                    .map_err(|err| err.to_string())
+            }),
+            ["step"] => emu
+                .tick()
+                .map(|_| format!("pc={:04X}", emu.get_pc()))
+                .map_err(|err: ExecError| err.to_string()),
+            ["skip"] => {
+                emu.skip_current();
+                Ok(format!("pc={:04X}", emu.get_pc()))
+            }
+            ["continue"] => {
+                self.running = true;
+                return None;
+            }
+            ["pause"] => Ok(format!("stopped pause {:04X}", emu.get_pc())),
+            [] => Err(String::from("empty command")),
+            _ => Err(format!("unknown command {}", line.trim())),
+        };
+
+        Some(match reply {
+            Ok(text) if text.is_empty() => String::from("ok"),
+            Ok(text) => format!("ok {}", text),
+            Err(err) => format!("err {}", err),
+        })
+    }
+
+    // a client that went away just stops getting replies
+    fn reply(&mut self, line: String) {
+        if let Some(client) = self.client.as_mut() {
+            if writeln!(client, "{}", line).is_err() {
+                self.client = None;
+            }
+        }
+    }
+}
+
+// accept clients one after another, forwarding each line they send to the main loop
+fn listen(listener: TcpListener, sender: Sender<Message>) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        if sender.send(Message::Connected(writer)).is_err() {
+            return;
+        }
+
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            if sender.send(Message::Line(line)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// one line with pc, I, the timers and every V register; also used by crash reports
+pub fn registers(emu: &Emu) -> String {
+    let mut text = format!(
+        "pc={:04X} i={:04X} dt={:02X} st={:02X}",
+        emu.get_pc(),
+        emu.get_i(),
+        emu.delay_timer(),
+        emu.sound_timer()
+    );
+    for reg in Reg::all() {
+        text += &format!(" v{:x}={:02X}", reg.index(), emu.get_register(reg));
+    }
+    text
+}
+
+// hex number with an optional 0x prefix
+fn parse(number: &str) -> Result<u16, String> {
+    let digits = number.trim_start_matches("0x").trim_start_matches("0X");
+    u16::from_str_radix(digits, 16).map_err(|_| format!("{} is not a hex number", number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    // a debugger connected to a server on a port of the OS's choosing
+    struct Session {
+        server: DebugServer,
+        emu: Emu,
+        client: TcpStream,
+        replies: Receiver<String>,
+    }
+
+    impl Session {
+        fn new(rom: &[u8]) -> Self {
+            let server = DebugServer::new(0).unwrap();
+            let client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+            let reader = BufReader::new(client.try_clone().unwrap());
+            let (sender, replies) = mpsc::channel();
+            thread::spawn(move || {
+                for line in reader.lines().map_while(Result::ok) {
+                    if sender.send(line).is_err() {
+                        return;
+                    }
+                }
+            });
+
+            let mut emu = Emu::new();
+            emu.load(rom).unwrap();
+            Self {
+                server,
+                emu,
+                client,
+                replies,
+            }
+        }
+
+        // send `command` and run frames until the reply comes back
+        fn send(&mut self, command: &str) -> String {
+            writeln!(self.client, "{}", command).unwrap();
+            for _ in 0..500 {
+                self.server.run_frame(&mut self.emu, 10);
+                if let Ok(reply) = self.replies.recv_timeout(Duration::from_millis(10)) {
+                    return reply;
+                }
+            }
+            panic!("no reply to {}", command);
+        }
+    }
+
+    #[test]
+    fn break_continue_reg_mem() {
+        // 0x200: V0 = 5, 0x202: V0 += 1, 0x204: loop forever
+        let mut session = Session::new(&[0x60, 0x05, 0x70, 0x01, 0x12, 0x04]);
+
+        assert_eq!(session.send("break 204"), "ok");
+        assert_eq!(session.send("continue"), "ok stopped breakpoint 0204");
+        let reg = session.send("reg");
+        assert!(reg.starts_with("ok pc=0204 "), "{}", reg);
+        assert!(reg.contains(" v0=06 "), "{}", reg);
+        assert_eq!(session.send("mem 0x200 4"), "ok 60 05 70 01");
+        assert_eq!(
+            session.send("mem FFF 2"),
+            format!("err {}", session.emu.read_memory(0xFFF, 2).unwrap_err())
+        );
+        assert_eq!(session.send("bogus"), "err unknown command bogus");
+    }
+
+    #[test]
+    fn beeping_follows_silent() {
+        for silent in [false, true] {
+            // ST = V0 = 0x20, then loop forever
+            let mut session = Session::new(&[0x60, 0x20, 0xF0, 0x18, 0x12, 0x04]);
+            session.emu.set_silent(silent);
+            // `continue` isn't answered while it runs, so drive the frames directly
+            writeln!(session.client, "continue").unwrap();
+            let mut beeped = false;
+            for _ in 0..10 {
+                beeped |= session.server.run_frame(&mut session.emu, 10).beeping;
+                thread::sleep(Duration::from_millis(5));
+            }
+            assert!(session.emu.sound_timer() > 0);
+            assert_eq!(beeped, !silent);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use chip8_core::Emu;
+
+// phosphor-style fading for `--fade-rate`: a pixel that goes dark keeps glowing for a few frames,
+// which hides the flicker of games that erase and redraw their sprites every frame
+pub struct Fade {
+    // how much brightness a dark pixel loses per frame; 255 goes dark straight away (no fade at all)
+    rate: u8,
+    width: usize,
+    // brightness of every display pixel, 255 while lit
+    intensity: Vec<u8>,
+    // the planes each pixel was last lit in, so it fades in the color it had
+    planes: Vec<u8>,
+}
+
+impl Fade {
+    pub fn new(rate: u8) -> Self {
+        Self {
+            rate,
+            width: 0,
+            intensity: Vec::new(),
+            planes: Vec::new(),
+        }
+    }
+
+    // bring the buffer up to date with the display after a frame has run
+    pub fn update(&mut self, emu: &Emu) {
+        let len = emu.get_display().len();
+        // switching resolution clears the display anyway, so there's nothing worth fading
+        if self.width != emu.display_width() || self.intensity.len() != len {
+            self.width = emu.display_width();
+            self.intensity = vec![0; len];
+            self.planes = vec![0; len];
+        }
+
+        for i in 0..len {
+            let planes = emu.plane_bits(i);
+            if planes != 0 {
+                self.intensity[i] = 255;
+                self.planes[i] = planes;
+            } else {
+                self.intensity[i] = self.intensity[i].saturating_sub(self.rate);
+            }
+        }
+    }
+
+    // the (x, y) position, last `plane_bits` and brightness of every pixel that is dark but still glowing
+    pub fn fading(&self) -> impl Iterator<Item = (u32, u32, u8, u8)> + '_ {
+        let width = self.width;
+
+        self.intensity
+            .iter()
+            .zip(&self.planes)
+            .enumerate()
+            .filter(|(_, (intensity, _))| (1..255).contains(*intensity))
+            .map(move |(i, (intensity, planes))| {
+                ((i % width) as u32, (i / width) as u32, *planes, *intensity)
+            })
+    }
+}
@@ -0,0 +1,150 @@
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+
+// how far a stick has to be pushed before it counts as a direction
+const STICK_THRESHOLD: f32 = 0.5;
+
+// gamepad input through gilrs (`--features gamepad`), for controllers SDL2 doesn't handle well
+// only one pad plays at a time: the first one to press a button, until it's disconnected
+//
+//  pad                       chip-8
+//  d-pad / left stick        2 4 6 8 (up left right down)
+//  south (A / cross)         5
+//  east (B / circle)         0
+//  west (X / square)         A
+//  north (Y / triangle)      B
+//  start                     1
+//  select                    F
+pub struct Gamepad {
+    gilrs: Gilrs,
+    active: Option<GamepadId>,
+    // how many of the active pad's buttons and axes hold each CHIP-8 key, so they can be let go if it
+    // disconnects
+    held: [u8; 16],
+    // the direction each left stick axis is pushed in, as a CHIP-8 key
+    stick_x: Option<usize>,
+    stick_y: Option<usize>,
+}
+
+impl Gamepad {
+    // the error is only for printing (gilrs's own error type is very large)
+    pub fn new() -> Result<Self, String> {
+        Ok(Self {
+            gilrs: Gilrs::new().map_err(|err| err.to_string())?,
+            active: None,
+            held: [0; 16],
+            stick_x: None,
+            stick_y: None,
+        })
+    }
+
+    // CHIP-8 key presses (`true`) and releases (`false`) since the last call, in the order they happened
+    // like keyboard keys, two buttons mapped to the same CHIP-8 key each report their own press and release
+    pub fn poll(&mut self) -> Vec<(usize, bool)> {
+        let mut changes = Vec::new();
+
+        while let Some(event) = self.gilrs.next_event() {
+            if event.event == EventType::Disconnected {
+                if self.active == Some(event.id) {
+                    self.release_all(&mut changes);
+                    self.active = None;
+                }
+                continue;
+            }
+            // a pad takes over by pressing a button, so a resting stick on another pad can't claim it
+            match self.active {
+                Some(id) if id != event.id => continue,
+                None if !matches!(event.event, EventType::ButtonPressed(..)) => continue,
+                _ => self.active = Some(event.id),
+            }
+
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(key) = button_key(button) {
+                        self.set(key, true, &mut changes);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(key) = button_key(button) {
+                        self.set(key, false, &mut changes);
+                    }
+                }
+                // gilrs reports up as positive
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
+                    let key = direction(value, 0x6, 0x4);
+                    let old = std::mem::replace(&mut self.stick_x, key);
+                    self.steer(old, key, &mut changes);
+                }
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => {
+                    let key = direction(value, 0x2, 0x8);
+                    let old = std::mem::replace(&mut self.stick_y, key);
+                    self.steer(old, key, &mut changes);
+                }
+                _ => (),
+            }
+        }
+
+        changes
+    }
+
+    // one button or axis pressed or released `key`; releases of keys this pad isn't holding are dropped
+    fn set(&mut self, key: usize, pressed: bool, changes: &mut Vec<(usize, bool)>) {
+        if pressed {
+            self.held[key] += 1;
+        } else if self.held[key] > 0 {
+            self.held[key] -= 1;
+        } else {
+            return;
+        }
+        changes.push((key, pressed));
+    }
+
+    // a stick axis moved from pointing at `old` to pointing at `new`
+    fn steer(&mut self, old: Option<usize>, new: Option<usize>, changes: &mut Vec<(usize, bool)>) {
+        if old == new {
+            return;
+        }
+        if let Some(key) = old {
+            self.set(key, false, changes);
+        }
+        if let Some(key) = new {
+            self.set(key, true, changes);
+        }
+    }
+
+    fn release_all(&mut self, changes: &mut Vec<(usize, bool)>) {
+        for key in 0..16 {
+            while self.held[key] > 0 {
+                self.set(key, false, changes);
+            }
+        }
+        self.stick_x = None;
+        self.stick_y = None;
+    }
+}
+
+fn button_key(button: Button) -> Option<usize> {
+    match button {
+        Button::DPadUp => Some(0x2),
+        Button::DPadLeft => Some(0x4),
+        Button::DPadRight => Some(0x6),
+        Button::DPadDown => Some(0x8),
+        Button::South => Some(0x5),
+        Button::East => Some(0x0),
+        Button::West => Some(0xA),
+        Button::North => Some(0xB),
+        Button::Start => Some(0x1),
+        Button::Select => Some(0xF),
+        _ => None,
+    }
+}
+
+// `positive` or `negative` once the stick is pushed far enough that way
+fn direction(value: f32, positive: usize, negative: usize) -> Option<usize> {
+    if value > STICK_THRESHOLD {
+        Some(positive)
+    } else if value < -STICK_THRESHOLD {
+        Some(negative)
+    } else {
+        None
+    }
+}
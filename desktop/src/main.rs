@@ -1,126 +1,936 @@
+mod audio;
+mod crash;
+mod debug_server;
+mod fade;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod menu;
+mod options;
+mod overlay;
+mod recorder;
+mod stats;
+mod watcher;
+
+use audio::{Beeper, WavRecorder};
 use chip8_core::*;
+use crash::write_crash_report;
+use debug_server::DebugServer;
+use fade::Fade;
+#[cfg(feature = "gamepad")]
+use gamepad::Gamepad;
+use menu::{list_roms, pick_rom, ROM_DIR};
+use options::{Options, USAGE};
+use overlay::draw_keypad;
+use recorder::GifRecorder;
+use stats::FrameStats;
+use watcher::RomWatcher;
 
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::keyboard::{Keycode, Mod};
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
+use sdl2::EventPump;
 
 use std::env;
-use std::fs::File;
-use std::io::Read;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const WINDOW_TITLE: &str = "Chip-8 Emulator";
 
-// arbitrary value; scale factor
+// arbitrary value; scale factor for the default window size (and GIF recordings)
 const SCALE: u32 = 15;
 
 const WINDOW_WIDTH: u32 = (SCREEN_WIDTH as u32) * SCALE;
 const WINDOW_HEIGHT: u32 = (SCREEN_HEIGHT as u32) * SCALE;
 
-// arbitrary value; CHIP-8 spec doesn't say anything about how fast clock speed should be
-const TICKS_PER_FRAME: usize = 10;
+// fills the window around the game screen when the window's shape doesn't match the display's
+const LETTERBOX_COLOR: Color = Color::RGB(0, 0, 0);
+
+// colors for lit and unlit pixels
+const FG_COLOR: Color = Color::RGB(255, 255, 255);
+const BG_COLOR: Color = Color::RGB(0, 0, 0);
+
+// default XO-CHIP palette, indexed by the planes a pixel is lit in (`Emu::plane_bits`):
+//  0 = no planes (background), 1 = first plane, 2 = second plane, 3 = both planes
+// CHIP-8 and SUPER-CHIP games only use the first plane so only the first two entries show up
+const DEFAULT_PALETTE: [Color; 4] = [
+    BG_COLOR,
+    FG_COLOR,
+    Color::RGB(170, 170, 170),
+    Color::RGB(85, 85, 85),
+];
+
+// visual beep: a thin dim border rather than a full-screen flash so it's not harsh on the eyes
+const BEEP_COLOR: Color = Color::RGB(96, 64, 0);
+const BEEP_BORDER: u32 = 6;
+
+// where ROMs are loaded and where RAM ends, for listing what was loaded
+const START: usize = 0x200;
+const RAM_END: usize = 0x1000;
+
+// the window is presented (and the timers tick) at 60 Hz
+const FRAME_HZ: u32 = 60;
+
+// arbitrary value (10 instructions per frame); CHIP-8 spec doesn't say anything about how fast clock speed should be
+const DEFAULT_CPU_HZ: u32 = 600;
+// how much PageUp / PageDown change the clock speed by
+const CPU_HZ_STEP: u32 = 100;
 
 fn main() {
-    let args: Vec<_> = env::args().collect();
+    // args[0] is the name of the program
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            println!("{}", err);
+            println!("{}", USAGE);
+            return;
+        }
+    };
 
-    // accept only path to game otherwise exit with error
-    if args.len() != 2 {
-        println!("Usage: cargo run path/to/game");
-        return;
+    // instantiate emulation object
+    // the ROM is loaded before opening a window so a bad file doesn't flash one up
+    let mut builder = EmuBuilder::new().variant(options.variant.unwrap_or_default());
+    if let Some(quirks) = options.quirks {
+        builder = builder.quirks(quirks);
+    }
+    let mut chip8 = builder.build();
+    // games can behave differently under other quirks so make it obvious which are in effect
+    println!("{:?}", chip8.quirks());
+    chip8.set_silent(options.silent);
+
+    // the core ticks the timers off the instruction count, so they stay at 60 Hz whatever the clock speed
+    chip8.set_clock_hz(options.cpu_hz.unwrap_or(DEFAULT_CPU_HZ));
+    println!("running at {} Hz", chip8.clock_hz());
+    // carries the fraction of an instruction left over when the clock speed isn't a multiple of 60
+    let mut cycle_acc = 0;
+
+    // in menu mode this is filled in once the window is open and the player has picked a game
+    let mut rom_path = options.rom.clone().unwrap_or_default();
+    // keys the save state slots, so a different game (or a rebuilt one) under the same path gets its own
+    let mut rom_sum = 0;
+    if !options.menu {
+        let buffer = match read_rom(&rom_path) {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                println!("unable to open {}: {}", rom_path, err);
+                return;
+            }
+        };
+        if let Err(err) = load_rom(&mut chip8, &rom_path, &buffer, &options) {
+            report(&err.into());
+            return;
+        }
+        rom_sum = rom_checksum(&buffer);
+        load_flags(&mut chip8, &rom_path);
+
+        // listing mode: no window, just the disassembly
+        if let Some(out) = options.disasm.as_deref() {
+            if let Err(err) = write_disassembly(&chip8, &rom_path, buffer.len(), out) {
+                println!("unable to write disassembly to {}: {}", out, err);
+            }
+            return;
+        }
     }
+    // checked up front so an empty or missing directory doesn't flash a window up
+    let roms = if options.menu {
+        match list_roms(ROM_DIR) {
+            Ok(roms) if !roms.is_empty() => roms,
+            Ok(_) => {
+                println!("no ROMs found in {}/", ROM_DIR);
+                return;
+            }
+            Err(err) => {
+                println!("unable to read {}/: {}", ROM_DIR, err);
+                return;
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    // an external debugger drives emulation from here on; the game starts paused
+    let mut debugger = match options.debug_port.map(DebugServer::new) {
+        Some(Ok(server)) => {
+            println!("debug server listening on 127.0.0.1:{}", server.port());
+            Some(server)
+        }
+        Some(Err(err)) => {
+            println!("unable to start debug server: {}", err);
+            return;
+        }
+        None => None,
+    };
 
     // setup SDL window
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
-    let window = video_subsystem
-        .window("Chip-8 Emulator", WINDOW_WIDTH, WINDOW_HEIGHT)
+    let (window_width, window_height) = options.window.unwrap_or((WINDOW_WIDTH, WINDOW_HEIGHT));
+    let mut window = video_subsystem
+        .window(WINDOW_TITLE, window_width, window_height)
         .position_centered()
+        .resizable()
         .opengl()
         .build()
         .unwrap();
+    window
+        .set_minimum_size(SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
+        .unwrap();
 
     let mut canvas = window.into_canvas().present_vsync().build().unwrap();
     canvas.clear();
     canvas.present();
 
+    // a missing audio device isn't fatal; the game just runs silently
+    let beeper = if options.silent {
+        None
+    } else {
+        match Beeper::new(&sdl_context, options.waveform) {
+            Ok(beeper) => Some(beeper),
+            Err(err) => {
+                println!("unable to open audio device: {}", err);
+                None
+            }
+        }
+    };
+    let mut muted = options.mute;
+    let mut wav = match options
+        .record_audio
+        .as_deref()
+        .map(|path| WavRecorder::new(path, options.waveform))
+    {
+        Some(Ok(wav)) => Some(wav),
+        Some(Err(err)) => {
+            println!("unable to record audio: {}", err);
+            None
+        }
+        None => None,
+    };
+    let mut show_keys = options.show_keys;
+    let mut crt = options.crt;
+    let mut fade = options.fade_rate.map(Fade::new);
+
     // poll for events every game loop
     let mut event_pump = sdl_context.event_pump().unwrap();
 
-    // instantiate emulation object
-    let mut chip8 = Emu::new();
+    // colors given with --palette replace the defaults in order
+    let mut palette = DEFAULT_PALETTE;
+    for (slot, [r, g, b]) in palette.iter_mut().zip(options.palette.iter()) {
+        *slot = Color::RGB(*r, *g, *b);
+    }
 
-    // args[0] is the name of the file
-    let mut rom = File::open(&args[1]).expect("unable to open file");
-    let mut buffer = Vec::new();
+    if options.menu
+        && !choose_rom(
+            &roms,
+            &options,
+            &mut chip8,
+            &mut rom_path,
+            &mut rom_sum,
+            &mut event_pump,
+            &mut canvas,
+        )
+    {
+        // no game ran, but the audio recording was already started
+        let audio = wav.zip(options.record_audio.as_deref());
+        cleanup(&chip8, &rom_path, chip8.rpl_flags(), None, audio);
+        return;
+    }
+
+    // the flags as last written to disk; FX75 changing them is what triggers a save
+    let mut saved_flags = chip8.rpl_flags();
 
-    rom.read_to_end(&mut buffer).unwrap();
-    chip8.load(&buffer);
+    // developer mode: pick up rebuilt ROMs without restarting
+    let mut watcher = options.watch.then(|| RomWatcher::new(&rom_path));
+
+    // both players' key clusters (and the gamepad) feed the same keypad
+    let mut held = HeldKeys::new();
+
+    #[cfg(feature = "gamepad")]
+    let mut gamepad = match Gamepad::new() {
+        Ok(gamepad) => Some(gamepad),
+        Err(err) => {
+            println!("gamepads unavailable: {}", err);
+            None
+        }
+    };
+
+    let mut stats = options.stats.then(|| FrameStats::new(chip8.cycle_count()));
+
+    // active gif recording (toggled with F9)
+    let mut recorder: Option<GifRecorder> = None;
+
+    if options.teach {
+        println!("teach mode: press Enter to run the next instruction");
+    }
 
     'gameloop: loop {
+        let mut back_to_menu = false;
         for event in event_pump.poll_iter() {
             match event {
+                // in menu mode Escape leaves the game instead of the emulator
+                Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } if options.menu => back_to_menu = true,
                 Event::Quit { .. }
                 | Event::KeyDown {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => break 'gameloop,
                 Event::KeyDown {
-                    keycode: Some(key), ..
+                    keycode: Some(Keycode::F9),
+                    repeat: false,
+                    ..
+                } => match recorder.take() {
+                    Some(rec) => save_recording(&rec),
+                    None => {
+                        println!("recording started");
+                        recorder = Some(GifRecorder::new());
+                    }
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::F1),
+                    repeat: false,
+                    ..
+                } => show_keys = !show_keys,
+                Event::KeyDown {
+                    keycode: Some(Keycode::F2),
+                    repeat: false,
+                    ..
+                } => crt = !crt,
+                Event::KeyDown {
+                    keycode: Some(key @ (Keycode::PageUp | Keycode::PageDown)),
+                    ..
+                } => {
+                    let hz = if key == Keycode::PageUp {
+                        chip8.clock_hz() + CPU_HZ_STEP
+                    } else {
+                        chip8.clock_hz().saturating_sub(CPU_HZ_STEP).max(FRAME_HZ)
+                    };
+                    chip8.set_clock_hz(hz);
+                    cycle_acc = 0;
+                    println!("running at {} Hz", hz);
+                }
+                // in teach mode the game only moves when Enter is pressed (held down, it keeps stepping)
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return),
+                    ..
+                } if options.teach => {
+                    if let Err(err) = teach_step(&mut chip8) {
+                        crashed(&chip8, &options, &rom_path, rom_sum, err);
+                        break 'gameloop;
+                    }
+                }
+                // F5 - F8 are four save slots; shift loads instead of saving
+                Event::KeyDown {
+                    keycode: Some(key @ (Keycode::F5 | Keycode::F6 | Keycode::F7 | Keycode::F8)),
+                    keymod,
+                    repeat: false,
+                    ..
+                } => {
+                    let slot = match key {
+                        Keycode::F5 => 1,
+                        Keycode::F6 => 2,
+                        Keycode::F7 => 3,
+                        _ => 4,
+                    };
+                    if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        load_slot(&mut chip8, &rom_path, rom_sum, slot);
+                        cycle_acc = 0;
+                    } else {
+                        save_slot(&chip8, &rom_path, rom_sum, slot);
+                    }
+                }
+                // ctrl+M since a plain M belongs to player two's keypad
+                Event::KeyDown {
+                    keycode: Some(Keycode::M),
+                    keymod,
+                    repeat: false,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    muted = !muted;
+                    println!("{}", if muted { "MUTED" } else { "UNMUTED" });
+                }
+                Event::KeyDown {
+                    keycode: Some(key),
+                    repeat: false,
+                    ..
                 } => {
                     if let Some(k) = key2btn(key) {
-                        chip8.keypress(k, true);
+                        send_key(&mut chip8, k, held.press(k));
                     }
                 }
                 Event::KeyUp {
                     keycode: Some(key), ..
                 } => {
                     if let Some(k) = key2btn(key) {
-                        chip8.keypress(k, false);
+                        send_key(&mut chip8, k, held.release(k));
                     }
                 }
                 _ => (),
             }
         }
 
-        // clock speed is 10 ticks per frame (arbitrary value)
-        for _ in 0..TICKS_PER_FRAME {
-            chip8.tick();
+        #[cfg(feature = "gamepad")]
+        for (k, pressed) in gamepad.as_mut().map(Gamepad::poll).unwrap_or_default() {
+            let down = if pressed {
+                held.press(k)
+            } else {
+                held.release(k)
+            };
+            send_key(&mut chip8, k, down);
+        }
+
+        if back_to_menu {
+            if !choose_rom(
+                &roms,
+                &options,
+                &mut chip8,
+                &mut rom_path,
+                &mut rom_sum,
+                &mut event_pump,
+                &mut canvas,
+            ) {
+                break 'gameloop;
+            }
+            watcher = options.watch.then(|| RomWatcher::new(&rom_path));
+            saved_flags = chip8.rpl_flags();
+            held = HeldKeys::new();
+            cycle_acc = 0;
+            continue;
+        }
+
+        if let Some(watcher) = watcher.as_mut() {
+            if watcher.changed() {
+                match read_rom(&rom_path) {
+                    Ok(buffer) => {
+                        chip8.reset();
+                        match load_rom(&mut chip8, &rom_path, &buffer, &options) {
+                            Ok(()) => {
+                                rom_sum = rom_checksum(&buffer);
+                                println!("reloaded ROM");
+                            }
+                            Err(err) => report(&err.into()),
+                        }
+                    }
+                    Err(err) => println!("unable to reload ROM: {}", err),
+                }
+            }
+        }
+
+        // the timers run off the instruction count, so a teaching frame with no instructions leaves them alone
+        let ticks = if options.teach {
+            0
+        } else {
+            frame_ticks(chip8.clock_hz(), &mut cycle_acc)
+        };
+        let frame = match debugger.as_mut() {
+            Some(server) => Ok(server.run_frame(&mut chip8, ticks)),
+            None => chip8.run_frame(ticks),
+        };
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                crashed(&chip8, &options, &rom_path, rom_sum, err);
+                break 'gameloop;
+            }
+        };
+
+        // saved as soon as they change so a high score survives the emulator being killed
+        if chip8.rpl_flags() != saved_flags {
+            saved_flags = chip8.rpl_flags();
+            save_flags(&saved_flags, &rom_path);
+        }
+
+        // muting only silences the output, the sound timer keeps counting down as normal
+        if let Some(beeper) = beeper.as_ref() {
+            beeper.set_playing(!muted && frame.beeping);
+        }
+        // a write error ends the recording but not the game
+        if let Some(Err(err)) = wav.as_mut().map(|wav| wav.push_frame(frame.beeping)) {
+            println!("unable to record audio: {}", err);
+            wav = None;
+        }
+
+        // game draws at 60 Hz; overlays go on top of the game before the frame is presented
+        if let Some(fade) = fade.as_mut() {
+            fade.update(&chip8);
         }
+        draw_game(&chip8, &mut canvas, &palette, crt, fade.as_ref());
+        if options.flash_on_beep && chip8.sound_timer() > 0 {
+            draw_beep_border(&mut canvas);
+        }
+        if show_keys {
+            let (width, height) = canvas.output_size().unwrap();
+            draw_keypad(&chip8, &mut canvas, width, height);
+        }
+        // with vsync on, this is what waits for the next refresh
+        canvas.present();
 
-        // timers tick once per frame
-        chip8.tick_timers();
+        // the title bar needs no font, and is out of the way of the game
+        if let Some(text) = stats.as_mut().and_then(|s| s.frame(chip8.cycle_count())) {
+            let title = format!("{} - {}", WINDOW_TITLE, text);
+            canvas.window_mut().set_title(&title).unwrap();
+        }
 
-        // game draws at 60 Hz
-        draw_screen(&chip8, &mut canvas);
+        // append the frame that was just drawn; stop automatically once the recording is full
+        if let Some(rec) = recorder.as_mut() {
+            let frame = chip8.display_as_rgba(rgba(palette[1]), rgba(palette[0]));
+            if !rec.push(chip8.display_width(), frame) {
+                save_recording(rec);
+                recorder = None;
+            }
+        }
     }
+
+    let audio = wav.zip(options.record_audio.as_deref());
+    cleanup(&chip8, &rom_path, saved_flags, recorder.as_ref(), audio);
 }
 
-// draw loop
-fn draw_screen(emu: &Emu, canvas: &mut Canvas<Window>) {
-    // clear the canvas as black
-    canvas.set_draw_color(Color::RGB(0, 0, 0));
-    canvas.clear();
+// finish everything that still has to reach the disk, however the game loop ended (quitting, an
+// execution error, or backing out of the menu)
+fn cleanup(
+    chip8: &Emu,
+    rom_path: &str,
+    saved_flags: [u8; 16],
+    recorder: Option<&GifRecorder>,
+    audio: Option<(WavRecorder, &str)>,
+) {
+    // flags written by the very last instruction haven't been picked up by the per-frame check yet
+    if chip8.rpl_flags() != saved_flags {
+        save_flags(&chip8.rpl_flags(), rom_path);
+    }
 
-    let screen_buf = emu.get_display();
-    // set draw color to white, iterate through each point and see if it should be drawn
-    canvas.set_draw_color(Color::RGB(255, 255, 255));
-    for (i, pixel) in screen_buf.iter().enumerate() {
-        if *pixel {
-            // convert 1D array's index into a 2D (x, y) position
-            let x = (i % SCREEN_WIDTH) as u32;
-            let y = (i / SCREEN_WIDTH) as u32;
+    // a GIF still being recorded is saved rather than thrown away
+    if let Some(rec) = recorder {
+        save_recording(rec);
+    }
 
-            // draw rectangle at (x, y) scaled up by `SCALE` factor
-            let rect = Rect::new((x * SCALE) as i32, (y * SCALE) as i32, SCALE, SCALE);
-            canvas.fill_rect(rect).unwrap();
+    // the WAV header only gets its sizes once the recording is over
+    if let Some((wav, path)) = audio {
+        match wav.finish() {
+            Ok(()) => println!("saved audio to {}", path),
+            Err(err) => println!("unable to save audio: {}", err),
         }
     }
+}
 
-    canvas.present();
+// how many instructions to run this frame so that on average exactly `cpu_hz` run per second
+fn frame_ticks(cpu_hz: u32, cycle_acc: &mut u32) -> usize {
+    *cycle_acc += cpu_hz;
+    let ticks = *cycle_acc / FRAME_HZ;
+    *cycle_acc %= FRAME_HZ;
+    ticks as usize
+}
+
+// read the whole ROM file into memory
+fn read_rom(path: &str) -> io::Result<Vec<u8>> {
+    let mut rom = File::open(path)?;
+    let mut buffer = Vec::new();
+    rom.read_to_end(&mut buffer)?;
+
+    Ok(buffer)
+}
+
+// list the loaded ROM one instruction per line with its address and raw opcode
+// listing from RAM rather than the file means Intel HEX ROMs come out the same as binaries
+fn write_disassembly(emu: &Emu, path: &str, file_len: usize, out: &str) -> io::Result<()> {
+    let mut len = file_len.min(RAM_END - START);
+    // a .hex file's length says nothing about the program's, so stop after its last non-zero byte
+    if path.to_ascii_lowercase().ends_with(".hex") {
+        let ram = emu.read_memory(START as u16, RAM_END - START).unwrap();
+        len = ram
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |last| last + 1);
+    }
+    let rom = emu.read_memory(START as u16, len).unwrap();
+
+    let mut listing: Box<dyn Write> = match out {
+        "-" => Box::new(io::stdout()),
+        _ => Box::new(File::create(out)?),
+    };
+    for line in disassemble_rom(rom) {
+        writeln!(listing, "{}", line)?;
+    }
+    listing.flush()
+}
+
+// show the menu until the player picks a ROM that loads, starting it from a fresh machine
+// returns false if they back out of the menu instead
+fn choose_rom(
+    roms: &[String],
+    options: &Options,
+    chip8: &mut Emu,
+    rom_path: &mut String,
+    rom_sum: &mut u64,
+    event_pump: &mut EventPump,
+    canvas: &mut Canvas<Window>,
+) -> bool {
+    while let Some(path) = pick_rom(roms, event_pump, canvas) {
+        chip8.reset();
+        match read_rom(&path) {
+            Ok(buffer) => match load_rom(chip8, &path, &buffer, options) {
+                Ok(()) => {
+                    *rom_path = path;
+                    *rom_sum = rom_checksum(&buffer);
+                    load_flags(chip8, rom_path);
+                    return true;
+                }
+                Err(err) => report(&err.into()),
+            },
+            Err(err) => println!("unable to open {}: {}", path, err),
+        }
+    }
+    false
+}
+
+// copy a ROM into the emulator; files ending in .hex are read as Intel HEX, anything else as a raw binary
+// without --variant the variant (and its quirks, unless --quirks is given) is guessed from the ROM
+fn load_rom(
+    chip8: &mut Emu,
+    path: &str,
+    buffer: &[u8],
+    options: &Options,
+) -> Result<(), LoadError> {
+    if path.to_ascii_lowercase().ends_with(".hex") {
+        // anything that isn't text can't be valid HEX either and will fail to parse
+        chip8.load_hex(&String::from_utf8_lossy(buffer))?;
+    } else {
+        chip8.load(buffer)?;
+    }
+
+    if options.variant.is_none() {
+        // read back from RAM so HEX files are checked as the bytes they decode to
+        let variant = detect_variant(chip8.read_memory(START as u16, RAM_END - START).unwrap());
+        if variant != chip8.variant() {
+            chip8.set_variant(variant);
+            if let Some(quirks) = options.quirks {
+                chip8.set_quirks(quirks);
+            }
+            println!("detected {:?}: {:?}", variant, chip8.quirks());
+        }
+    }
+    Ok(())
+}
+
+// explain why emulation stopped, with a hint at what the player can do about it
+fn report(err: &Chip8Error) {
+    println!("{}", err);
+
+    let hint = match err {
+        Chip8Error::Load(LoadError::Empty) => "the file has nothing in it; check the path",
+        Chip8Error::Load(LoadError::TooLarge { .. }) => "check that the file is a CHIP-8 ROM",
+        Chip8Error::Load(
+            LoadError::MalformedHex { .. }
+            | LoadError::HexOutOfRange { .. }
+            | LoadError::MalformedDump { .. },
+        ) => "check that the file is Intel HEX output from a CHIP-8 assembler",
+        Chip8Error::Exec(ExecError::UnknownOpcode { .. }) => {
+            "the ROM may be corrupt or written for a different CHIP-8 variant"
+        }
+        Chip8Error::Exec(ExecError::StackOverflow { .. } | ExecError::StackUnderflow { .. }) => {
+            "the game's subroutine calls don't balance out; this is usually a bug in the ROM"
+        }
+        Chip8Error::Exec(ExecError::ProtectedWrite { .. }) => {
+            "the game wrote over the interpreter area; this is usually a bug in the ROM"
+        }
+        Chip8Error::Exec(ExecError::RamOutOfBounds { .. }) => {
+            "the game read or wrote past the end of memory; this is usually a bug in the ROM"
+        }
+        Chip8Error::OutOfBounds(_) | Chip8Error::SizeMismatch(_) => return,
+    };
+    println!("{}", hint);
+}
+
+// the game hit an error it can't carry on from: explain it and leave a crash report for bug reports
+fn crashed(chip8: &Emu, options: &Options, rom: &str, checksum: u64, err: ExecError) {
+    match write_crash_report(&options.crash_dir, chip8, rom, checksum, &err) {
+        Ok(path) => println!("crash report written to {}", path),
+        Err(err) => println!("unable to write crash report: {}", err),
+    }
+    report(&err.into());
+}
+
+// encode a finished gif recording into the working directory
+fn save_recording(recorder: &GifRecorder) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = format!("chip8-{}.gif", timestamp);
+
+    match recorder.save(&path, WINDOW_WIDTH as usize, WINDOW_HEIGHT as usize) {
+        Ok(()) => println!("saved recording to {}", path),
+        Err(err) => println!("unable to save recording: {}", err),
+    }
+}
+
+// run one instruction for --teach and print it along with everything it changed, e.g.
+//   0206  6105  V1 = 0x05
+//     V1 differs: 0x00 vs 0x05
+// pc is only mentioned when it didn't just move on to the next instruction
+fn teach_step(chip8: &mut Emu) -> Result<(), ExecError> {
+    let addr = chip8.get_pc();
+    let op = match chip8.read_memory(addr, 2) {
+        Ok(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]),
+        Err(_) => 0,
+    };
+    let instr = decode(op);
+    println!("{}", DisasmLine { addr, op, instr });
+
+    let mut before = Emu::new();
+    before.restore(&chip8.snapshot());
+    chip8.tick()?;
+
+    for change in before.diff(chip8) {
+        match change {
+            StateDiff::Pc { b, .. } if b == addr.wrapping_add(2) => (),
+            change => println!("  {}", change),
+        }
+    }
+    Ok(())
+}
+
+// SUPER-CHIP games keep high scores in the RPL user flags (FX75 / FX85), which are saved next to the ROM
+// as `<rom>.flags`: the 16 flag bytes as raw binary, flag 0 first
+fn flags_path(rom: &str) -> String {
+    format!("{}.flags", rom)
+}
+
+// games that have never saved any flags start with them all zero
+fn load_flags(chip8: &mut Emu, rom: &str) {
+    let path = flags_path(rom);
+    let flags = match fs::read(&path) {
+        Ok(bytes) => match bytes.try_into() {
+            Ok(flags) => flags,
+            Err(_) => {
+                println!("{} isn't 16 bytes long, ignoring it", path);
+                [0; 16]
+            }
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => [0; 16],
+        Err(err) => {
+            println!("unable to read {}: {}", path, err);
+            [0; 16]
+        }
+    };
+    chip8.set_rpl_flags(flags);
+}
+
+fn save_flags(flags: &[u8; 16], rom: &str) {
+    let path = flags_path(rom);
+    if let Err(err) = fs::write(&path, flags) {
+        println!("unable to save {}: {}", path, err);
+    }
+}
+
+// save states live next to the ROM, tagged with its checksum so states from a different game that was
+// at the same path can't be loaded: `pong.ch8` slot 1 is `pong.ch8.<checksum>.slot1.state`
+fn slot_path(rom: &str, checksum: u64, slot: u8) -> String {
+    format!("{}.{:016x}.slot{}.state", rom, checksum, slot)
+}
+
+fn save_slot(chip8: &Emu, rom: &str, checksum: u64, slot: u8) {
+    let path = slot_path(rom, checksum, slot);
+    let result = bincode::serialize(&chip8.snapshot())
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| fs::write(&path, bytes).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => println!("saved slot {} to {}", slot, path),
+        Err(err) => println!("unable to save slot {}: {}", slot, err),
+    }
+}
+
+// a missing or unreadable slot is reported and the game carries on as it was
+fn load_slot(chip8: &mut Emu, rom: &str, checksum: u64, slot: u8) {
+    let path = slot_path(rom, checksum, slot);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            println!("slot {} is empty", slot);
+            return;
+        }
+        Err(err) => {
+            println!("unable to read {}: {}", path, err);
+            return;
+        }
+    };
+
+    match bincode::deserialize::<EmuState>(&bytes) {
+        Ok(state) if chip8.restore(&state) => println!("loaded slot {} from {}", slot, path),
+        _ => println!("{} isn't a valid save state", path),
+    }
+}
+
+// convert an SDL color into the RGBA bytes used by `display_as_rgba`
+fn rgba(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, 255]
+}
+
+// where the game screen goes in the window: the display scaled as large as fits without stretching,
+// centered, with letterbox bars on the two sides that are left over
+struct Viewport {
+    left: f32,
+    top: f32,
+    // window pixels per display pixel; not a whole number unless the window happens to fit exactly
+    scale: f32,
+}
+
+impl Viewport {
+    fn new(canvas: &Canvas<Window>, emu: &Emu) -> Self {
+        let (window_width, window_height) = canvas.output_size().unwrap();
+        let (width, height) = (emu.display_width() as f32, emu.display_height() as f32);
+        let scale = (window_width as f32 / width).min(window_height as f32 / height);
+
+        Self {
+            left: (window_width as f32 - width * scale) / 2.0,
+            top: (window_height as f32 - height * scale) / 2.0,
+            scale,
+        }
+    }
+
+    // the window rect covering display pixels `x..x + w` and `y..y + h` (fractions allowed)
+    // edges are rounded separately so neighbouring pixels meet without gaps or overlaps
+    fn rect(&self, x: f32, y: f32, w: f32, h: f32) -> Rect {
+        let left = (self.left + x * self.scale).round();
+        let top = (self.top + y * self.scale).round();
+        let right = (self.left + (x + w) * self.scale).round();
+        let bottom = (self.top + (y + h) * self.scale).round();
+        Rect::new(
+            left as i32,
+            top as i32,
+            ((right - left) as u32).max(1),
+            ((bottom - top) as u32).max(1),
+        )
+    }
+}
+
+// draw the game screen into the back buffer; presenting is left to the main loop
+// `crt` adds a faint glow around lit pixels and a dark scanline gap under each row of pixels
+// `fade` draws pixels that recently went dark, fading from their color into the background
+fn draw_game(
+    emu: &Emu,
+    canvas: &mut Canvas<Window>,
+    palette: &[Color; 4],
+    crt: bool,
+    fade: Option<&Fade>,
+) {
+    // the bars first, then the background color only where the game screen goes
+    // SUPER-CHIP hires doubles the resolution so pixels are drawn at half the size
+    let view = Viewport::new(canvas, emu);
+    canvas.set_draw_color(LETTERBOX_COLOR);
+    canvas.clear();
+    canvas.set_draw_color(palette[0]);
+    canvas
+        .fill_rect(view.rect(
+            0.0,
+            0.0,
+            emu.display_width() as f32,
+            emu.display_height() as f32,
+        ))
+        .unwrap();
+
+    // the glow goes down first so the pixels themselves are drawn over it
+    if crt {
+        let bloom = 0.25;
+        for (x, y, planes) in lit_pixels(emu) {
+            canvas.set_draw_color(dim(palette[planes as usize], palette[0]));
+            let halo = view.rect(
+                x as f32 - bloom,
+                y as f32 - bloom,
+                1.0 + bloom * 2.0,
+                1.0 + bloom * 2.0,
+            );
+            canvas.fill_rect(halo).unwrap();
+        }
+    }
+
+    // leaving the bottom third of each row unlit makes the scanlines
+    let height = if crt { 2.0 / 3.0 } else { 1.0 };
+
+    for (x, y, planes, intensity) in fade.into_iter().flat_map(Fade::fading) {
+        canvas.set_draw_color(blend(palette[planes as usize], palette[0], intensity));
+        canvas
+            .fill_rect(view.rect(x as f32, y as f32, 1.0, height))
+            .unwrap();
+    }
+
+    // the color comes from which planes the pixel is lit in (only ever the first outside of XO-CHIP)
+    for (x, y, planes) in lit_pixels(emu) {
+        canvas.set_draw_color(palette[planes as usize]);
+
+        // draw rectangle at (x, y) scaled up to fill the viewport
+        let rect = view.rect(x as f32, y as f32, 1.0, height);
+        canvas.fill_rect(rect).unwrap();
+    }
+}
+
+// the (x, y) position and `plane_bits` of every lit pixel
+fn lit_pixels(emu: &Emu) -> impl Iterator<Item = (u32, u32, u8)> + '_ {
+    let width = emu.display_width();
+
+    (0..emu.get_display().len()).filter_map(move |i| {
+        let planes = emu.plane_bits(i);
+        // convert 1D array's index into a 2D (x, y) position
+        (planes != 0).then(|| ((i % width) as u32, (i / width) as u32, planes))
+    })
+}
+
+// a quarter of the way from the background to `color`, for the CRT glow
+fn dim(color: Color, background: Color) -> Color {
+    let mix = |c: u8, bg: u8| ((c as u16 + bg as u16 * 3) / 4) as u8;
+    Color::RGB(
+        mix(color.r, background.r),
+        mix(color.g, background.g),
+        mix(color.b, background.b),
+    )
+}
+
+// `amount` / 255 of the way from the background to `color`, for fading pixels
+fn blend(color: Color, background: Color, amount: u8) -> Color {
+    let mix = |c: u8, bg: u8| {
+        ((c as u32 * amount as u32 + bg as u32 * (255 - amount as u32)) / 255) as u8
+    };
+    Color::RGB(
+        mix(color.r, background.r),
+        mix(color.g, background.g),
+        mix(color.b, background.b),
+    )
+}
+
+// tint the edges of the window while the sound timer is running
+fn draw_beep_border(canvas: &mut Canvas<Window>) {
+    let (width, height) = canvas.output_size().unwrap();
+    canvas.set_draw_color(BEEP_COLOR);
+    let edges = [
+        Rect::new(0, 0, width, BEEP_BORDER),
+        Rect::new(0, (height - BEEP_BORDER) as i32, width, BEEP_BORDER),
+        Rect::new(0, 0, BEEP_BORDER, height),
+        Rect::new((width - BEEP_BORDER) as i32, 0, BEEP_BORDER, height),
+    ];
+    canvas.fill_rects(&edges).unwrap();
+}
+
+// queue key changes so taps shorter than a frame still reach the game in order
+// if the queue is full (e.g. while paused in the debugger) the change is applied straight away instead
+fn send_key(chip8: &mut Emu, key: usize, pressed: bool) {
+    if !chip8.queue_key_event(key, pressed) {
+        chip8.keypress(key, pressed);
+    }
 }
 
 // key mapper
+// two players can share the keyboard: each has their own cluster but both map onto the one CHIP-8 keypad
 fn key2btn(key: Keycode) -> Option<usize> {
+    player1_key2btn(key).or_else(|| player2_key2btn(key))
+}
+
+// player one: left side of the keyboard
+fn player1_key2btn(key: Keycode) -> Option<usize> {
     //  keyboard             CHIP-8
     //  +---+---+---+---+    +---+---+---+---+
     //  | 1 | 2 | 3 | 4 |    | 1 | 2 | 3 | C |
@@ -136,19 +946,136 @@ fn key2btn(key: Keycode) -> Option<usize> {
         Keycode::Num1 => Some(0x1),
         Keycode::Num2 => Some(0x2),
         Keycode::Num3 => Some(0x3),
-        Keycode::Num4 => Some(0x4),
-        Keycode::Q => Some(0x5),
-        Keycode::W => Some(0x6),
+        Keycode::Num4 => Some(0xC),
+        Keycode::Q => Some(0x4),
+        Keycode::W => Some(0x5),
         Keycode::E => Some(0x6),
         Keycode::R => Some(0xD),
         Keycode::A => Some(0x7),
         Keycode::S => Some(0x8),
         Keycode::D => Some(0x9),
         Keycode::F => Some(0xE),
-        Keycode::Z => Some(0xB),
+        Keycode::Z => Some(0xA),
         Keycode::X => Some(0x0),
         Keycode::C => Some(0xB),
         Keycode::V => Some(0xF),
         _ => None,
     }
 }
+
+// player two: the same shape shifted to the right side of the keyboard
+fn player2_key2btn(key: Keycode) -> Option<usize> {
+    //  keyboard             CHIP-8
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | 7 | 8 | 9 | 0 |    | 1 | 2 | 3 | C |
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | U | I | O | P |    | 4 | 5 | 6 | D |
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | J | K | L | ; |    | 7 | 8 | 9 | E |
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | M | , | . | / |    | A | 0 | B | F |
+    //  +---+---+---+---+    +---+---+---+---+
+
+    match key {
+        Keycode::Num7 => Some(0x1),
+        Keycode::Num8 => Some(0x2),
+        Keycode::Num9 => Some(0x3),
+        Keycode::Num0 => Some(0xC),
+        Keycode::U => Some(0x4),
+        Keycode::I => Some(0x5),
+        Keycode::O => Some(0x6),
+        Keycode::P => Some(0xD),
+        Keycode::J => Some(0x7),
+        Keycode::K => Some(0x8),
+        Keycode::L => Some(0x9),
+        Keycode::Semicolon => Some(0xE),
+        Keycode::M => Some(0xA),
+        Keycode::Comma => Some(0x0),
+        Keycode::Period => Some(0xB),
+        Keycode::Slash => Some(0xF),
+        _ => None,
+    }
+}
+
+// how many physical keys are holding each CHIP-8 key down
+// a CHIP-8 key stays pressed until every key mapped to it (from either player) is released
+struct HeldKeys([u8; 16]);
+
+impl HeldKeys {
+    fn new() -> Self {
+        Self([0; 16])
+    }
+
+    // returns the CHIP-8 key's new state
+    fn press(&mut self, btn: usize) -> bool {
+        self.0[btn] += 1;
+        true
+    }
+
+    fn release(&mut self, btn: usize) -> bool {
+        self.0[btn] = self.0[btn].saturating_sub(1);
+        self.0[btn] > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    #[test]
+    fn flags_round_trip_through_the_file() {
+        let rom = env::temp_dir().join(format!("chip8-flags-test-{}.ch8", std::process::id()));
+        let rom = rom.to_str().unwrap();
+        let mut flags = [0; 16];
+        flags[0] = 7;
+        flags[15] = 0xFF;
+        save_flags(&flags, rom);
+        assert_eq!(fs::read(flags_path(rom)).unwrap(), flags);
+
+        let mut chip8 = Emu::new();
+        load_flags(&mut chip8, rom);
+        assert_eq!(chip8.rpl_flags(), flags);
+
+        // a file of the wrong size is ignored
+        fs::write(flags_path(rom), [1, 2, 3]).unwrap();
+        load_flags(&mut chip8, rom);
+        assert_eq!(chip8.rpl_flags(), [0; 16]);
+
+        fs::remove_file(flags_path(rom)).unwrap();
+        load_flags(&mut chip8, rom);
+        assert_eq!(chip8.rpl_flags(), [0; 16]);
+    }
+
+    #[test]
+    fn cleanup_finishes_the_recording() {
+        let base = env::temp_dir().join(format!("chip8-cleanup-test-{}", std::process::id()));
+        let rom = format!("{}.ch8", base.to_str().unwrap());
+        let wav_path = format!("{}.wav", base.to_str().unwrap());
+
+        // a few frames of audio, then quit with a flag changed since the last save
+        let mut wav = WavRecorder::new(&wav_path, audio::Waveform::Square).unwrap();
+        for beeping in [true, true, false] {
+            wav.push_frame(beeping).unwrap();
+        }
+        let mut chip8 = Emu::new();
+        let mut flags = [0; 16];
+        flags[3] = 42;
+        chip8.set_rpl_flags(flags);
+        cleanup(&chip8, &rom, [0; 16], None, Some((wav, &wav_path)));
+
+        // the header's sizes match what was written
+        let bytes = fs::read(&wav_path).unwrap();
+        let size = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(size(4), bytes.len() - 8);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(size(40), bytes.len() - 44);
+        assert!(size(40) > 0);
+
+        assert_eq!(fs::read(flags_path(&rom)).unwrap(), flags);
+        fs::remove_file(&wav_path).unwrap();
+        fs::remove_file(flags_path(&rom)).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::EventPump;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// where the menu looks for games, relative to the working directory
+pub const ROM_DIR: &str = "roms";
+
+// sizes in window pixels; glyphs are 3x5 font pixels
+const FONT_PIXEL: u32 = 3;
+const ADVANCE: i32 = 4 * FONT_PIXEL as i32;
+const LINE_HEIGHT: i32 = 24;
+const MARGIN: i32 = 20;
+
+const TEXT_COLOR: Color = Color::RGB(200, 200, 200);
+const SELECTED_COLOR: Color = Color::RGB(0, 0, 0);
+const HIGHLIGHT_COLOR: Color = Color::RGB(230, 160, 40);
+const BG_COLOR: Color = Color::RGB(0, 0, 0);
+
+// every file in `dir`, sorted by name
+pub fn list_roms(dir: &str) -> io::Result<Vec<String>> {
+    let mut roms = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_type()
+                .map(|kind| kind.is_file())
+                .unwrap_or(false)
+        })
+        .filter_map(|entry| entry.path().to_str().map(String::from))
+        .collect::<Vec<_>>();
+    roms.sort();
+    Ok(roms)
+}
+
+// show `roms` as a list and wait for the player to pick one with the arrow keys and Enter
+// returns `None` if they close the window or press Escape instead
+pub fn pick_rom(
+    roms: &[String],
+    event_pump: &mut EventPump,
+    canvas: &mut Canvas<Window>,
+) -> Option<String> {
+    let (_, height) = canvas.output_size().unwrap();
+    // the title takes the first two lines
+    let visible = ((height as i32 - 2 * MARGIN) / LINE_HEIGHT - 2).max(1) as usize;
+    let mut selected: usize = 0;
+    let mut scroll = 0;
+
+    loop {
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => return None,
+                Event::KeyDown {
+                    keycode: Some(Keycode::Up),
+                    ..
+                } => selected = selected.checked_sub(1).unwrap_or(roms.len() - 1),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Down),
+                    ..
+                } => selected = (selected + 1) % roms.len(),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return),
+                    repeat: false,
+                    ..
+                } => return Some(roms[selected].clone()),
+                _ => (),
+            }
+        }
+
+        // keep the selection on screen
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + visible {
+            scroll = selected + 1 - visible;
+        }
+
+        canvas.set_draw_color(BG_COLOR);
+        canvas.clear();
+        draw_text(canvas, "SELECT A ROM", MARGIN, MARGIN, TEXT_COLOR);
+
+        for (row, rom) in roms.iter().enumerate().skip(scroll).take(visible) {
+            let y = MARGIN + (row - scroll + 2) as i32 * LINE_HEIGHT;
+            let name = Path::new(rom)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(rom);
+
+            let color = if row == selected {
+                let width = canvas.output_size().unwrap().0 - 2 * MARGIN as u32;
+                canvas.set_draw_color(HIGHLIGHT_COLOR);
+                canvas
+                    .fill_rect(Rect::new(MARGIN - 4, y - 4, width, LINE_HEIGHT as u32))
+                    .unwrap();
+                SELECTED_COLOR
+            } else {
+                TEXT_COLOR
+            };
+            draw_text(canvas, name, MARGIN, y, color);
+        }
+
+        // with vsync on, this is what paces the loop
+        canvas.present();
+    }
+}
+
+// `text` in the 3x5 font with its top left corner at (x, y); letters are shown in upper case
+fn draw_text(canvas: &mut Canvas<Window>, text: &str, x: i32, y: i32, color: Color) {
+    canvas.set_draw_color(color);
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i as i32 * ADVANCE;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    let rect = Rect::new(
+                        left + (col * FONT_PIXEL) as i32,
+                        y + (row as u32 * FONT_PIXEL) as i32,
+                        FONT_PIXEL,
+                        FONT_PIXEL,
+                    );
+                    canvas.fill_rect(rect).unwrap();
+                }
+            }
+        }
+    }
+}
+
+// 3x5 glyph for `c`, one row per entry with the left pixel in bit 2
+// covers what usually shows up in ROM file names; anything else is drawn as '?'
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        ' ' => [0; 5],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010],
+    }
+}
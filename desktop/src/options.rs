@@ -0,0 +1,240 @@
+use chip8_core::{Quirks, Variant, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+use crate::audio::Waveform;
+
+// command line options for the desktop frontend
+pub struct Options {
+    // path to the ROM to run; `None` in menu mode
+    pub rom: Option<String>,
+    // pick the ROM from a list of the files in `roms/` (also the default when no path is given)
+    pub menu: bool,
+    // tint the window border while the sound timer is running (visual beep)
+    pub flash_on_beep: bool,
+    // reload the ROM whenever the file changes on disk
+    pub watch: bool,
+    // start with the beep silenced (can be toggled while running)
+    pub mute: bool,
+    // never open an audio device at all (unlike --mute this can't be toggled)
+    pub silent: bool,
+    // shape of the beep, both played and recorded
+    pub waveform: Waveform,
+    // start with the keypad overlay visible (can be toggled while running)
+    pub show_keys: bool,
+    // start with CRT-style scanlines and glow (can be toggled while running)
+    pub crt: bool,
+    // how fast dark pixels fade out, in brightness lost per frame; `None` turns them off at once
+    pub fade_rate: Option<u8>,
+    // instruction set the ROM was written for; `None` guesses from the ROM (see `detect_variant`)
+    pub variant: Option<Variant>,
+    // interpreter quirks; `None` uses the variant's preset
+    pub quirks: Option<Quirks>,
+    // up to 4 RGB colors indexed by which planes a pixel is lit in (see `Emu::plane_bits`)
+    // missing entries fall back to the defaults
+    pub palette: Vec<[u8; 3]>,
+    // instructions per second; `None` uses the default speed
+    pub cpu_hz: Option<u32>,
+    // accept debugger commands on this TCP port (see `debug_server`)
+    pub debug_port: Option<u16>,
+    // write a disassembly of the ROM here ("-" for stdout) and exit instead of running it
+    pub disasm: Option<String>,
+    // capture the beep to this WAV file for the whole session
+    pub record_audio: Option<String>,
+    // only run one instruction per press of Enter, printing what it did
+    pub teach: bool,
+    // initial window size in pixels; `None` uses the default
+    pub window: Option<(u32, u32)>,
+    // show frames and instructions per second in the title bar
+    pub stats: bool,
+    // where crash reports are written when the game hits an error
+    pub crash_dir: String,
+}
+
+pub const USAGE: &str =
+    "Usage: cargo run [path/to/game | --menu] [--flash-on-beep] [--watch] [--mute] [--silent] [--waveform square|sine|triangle] [--show-keys] [--crt] [--fade-rate N] \
+[--variant chip8|schip|xochip] [--quirks vip|schip|xochip|modern] [--palette RRGGBB,RRGGBB,...] [--cpu-hz N] [--debug-server PORT] [--disasm OUT] [--record-audio OUT.wav] [--teach] [--window WxH] [--stats] [--crash-dir DIR]";
+
+impl Options {
+    // parse the arguments following the program name
+    // returns a message describing the problem on bad usage
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut rom = None;
+        let mut menu = false;
+        let mut flash_on_beep = false;
+        let mut watch = false;
+        let mut mute = false;
+        let mut silent = false;
+        let mut waveform = Waveform::Square;
+        let mut show_keys = false;
+        let mut crt = false;
+        let mut fade_rate = None;
+        let mut variant = None;
+        let mut quirks = None;
+        let mut palette = Vec::new();
+        let mut cpu_hz = None;
+        let mut debug_port = None;
+        let mut disasm = None;
+        let mut record_audio = None;
+        let mut teach = false;
+        let mut window = None;
+        let mut stats = false;
+        let mut crash_dir = String::from(".");
+
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--flash-on-beep" => flash_on_beep = true,
+                "--watch" => watch = true,
+                "--menu" => menu = true,
+                "--mute" => mute = true,
+                "--silent" => silent = true,
+                "--show-keys" => show_keys = true,
+                "--crt" => crt = true,
+                "--teach" => teach = true,
+                "--stats" => stats = true,
+                "--waveform" => waveform = parse_waveform(&value(&mut args, &arg)?)?,
+                "--variant" => variant = Some(parse_variant(&value(&mut args, &arg)?)?),
+                "--quirks" => quirks = Some(parse_quirks(&value(&mut args, &arg)?)?),
+                "--palette" => palette = parse_palette(&value(&mut args, &arg)?)?,
+                "--fade-rate" => fade_rate = Some(parse_fade_rate(&value(&mut args, &arg)?)?),
+                "--cpu-hz" => cpu_hz = Some(parse_hz(&value(&mut args, &arg)?)?),
+                "--disasm" => disasm = Some(value(&mut args, &arg)?),
+                "--record-audio" => record_audio = Some(value(&mut args, &arg)?),
+                "--crash-dir" => crash_dir = value(&mut args, &arg)?,
+                "--window" => window = Some(parse_window(&value(&mut args, &arg)?)?),
+                "--debug-server" => debug_port = Some(parse_port(&value(&mut args, &arg)?)?),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ if rom.is_some() => return Err(String::from("only one game can be given")),
+                _ => rom = Some(arg),
+            }
+        }
+
+        if menu && rom.is_some() {
+            return Err(String::from(
+                "--menu picks the game itself, so leave out the path",
+            ));
+        }
+        if rom.is_none() && disasm.is_some() {
+            return Err(String::from("--disasm needs the path to a game"));
+        }
+
+        Ok(Self {
+            menu: rom.is_none(),
+            rom,
+            flash_on_beep,
+            watch,
+            mute,
+            silent,
+            waveform,
+            show_keys,
+            crt,
+            fade_rate,
+            variant,
+            quirks,
+            palette,
+            cpu_hz,
+            debug_port,
+            disasm,
+            record_audio,
+            teach,
+            window,
+            stats,
+            crash_dir,
+        })
+    }
+}
+
+// the argument following an option that takes a value
+fn value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} needs a value", option))
+}
+
+fn parse_variant(name: &str) -> Result<Variant, String> {
+    match name {
+        "chip8" => Ok(Variant::Chip8),
+        "schip" => Ok(Variant::SuperChip),
+        "xochip" => Ok(Variant::XoChip),
+        _ => Err(format!("unknown variant {}", name)),
+    }
+}
+
+fn parse_waveform(name: &str) -> Result<Waveform, String> {
+    match name {
+        "square" => Ok(Waveform::Square),
+        "sine" => Ok(Waveform::Sine),
+        "triangle" => Ok(Waveform::Triangle),
+        _ => Err(format!(
+            "unknown waveform {} (expected square, sine, or triangle)",
+            name
+        )),
+    }
+}
+
+fn parse_quirks(name: &str) -> Result<Quirks, String> {
+    match name {
+        "vip" => Ok(Quirks::cosmac_vip()),
+        "schip" => Ok(Quirks::super_chip()),
+        "xochip" => Ok(Quirks::xo_chip()),
+        "modern" => Ok(Quirks::modern()),
+        _ => Err(format!(
+            "unknown quirks preset {} (expected vip, schip, xochip, or modern)",
+            name
+        )),
+    }
+}
+
+// anything slower than one instruction per frame isn't useful
+fn parse_hz(hz: &str) -> Result<u32, String> {
+    match hz.parse::<u32>() {
+        Ok(hz) if hz >= 60 => Ok(hz),
+        _ => Err(format!("{} is not a clock speed of at least 60 Hz", hz)),
+    }
+}
+
+// `WxH` in pixels, at least one window pixel per lores pixel
+fn parse_window(size: &str) -> Result<(u32, u32), String> {
+    let parsed = size
+        .split_once(['x', 'X'])
+        .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)));
+    match parsed {
+        Some((w, h)) if w >= SCREEN_WIDTH as u32 && h >= SCREEN_HEIGHT as u32 => Ok((w, h)),
+        _ => Err(format!(
+            "{} is not a window size of at least {}x{}",
+            size, SCREEN_WIDTH, SCREEN_HEIGHT
+        )),
+    }
+}
+
+// 0 - 255, clamped: 0 would leave ghosts on screen forever so it's bumped to 1, and anything
+// past 255 is the same as 255, which goes dark within a frame (i.e. no fade)
+fn parse_fade_rate(rate: &str) -> Result<u8, String> {
+    match rate.parse::<u32>() {
+        Ok(rate) => Ok(rate.clamp(1, 255) as u8),
+        Err(_) => Err(format!("{} is not a fade rate (0 - 255)", rate)),
+    }
+}
+
+fn parse_port(port: &str) -> Result<u16, String> {
+    port.parse()
+        .map_err(|_| format!("{} is not a port number", port))
+}
+
+// comma separated hex colors, with or without a leading '#'
+fn parse_palette(list: &str) -> Result<Vec<[u8; 3]>, String> {
+    let colors = list
+        .split(',')
+        .map(|hex| {
+            let hex = hex.trim().trim_start_matches('#');
+            match u32::from_str_radix(hex, 16) {
+                Ok(rgb) if hex.len() == 6 => Ok([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]),
+                _ => Err(format!("{} is not a RRGGBB color", hex)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if colors.len() > 4 {
+        return Err(String::from("a palette has at most 4 colors"));
+    }
+
+    Ok(colors)
+}
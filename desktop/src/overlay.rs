@@ -0,0 +1,91 @@
+use chip8_core::Emu;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+// CHIP-8 keypad layout, as the keys are arranged on the original hex keypad
+const KEYPAD: [[usize; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+// 4x5 hex digit glyphs (the same shapes as the CHIP-8 font); each row's bits are the left 4 pixels
+const GLYPHS: [[u8; 5]; 16] = [
+    [0xF0, 0x90, 0x90, 0x90, 0xF0], // 0
+    [0x20, 0x60, 0x20, 0x20, 0x70], // 1
+    [0xF0, 0x10, 0xF0, 0x80, 0xF0], // 2
+    [0xF0, 0x10, 0xF0, 0x10, 0xF0], // 3
+    [0x90, 0x90, 0xF0, 0x10, 0x10], // 4
+    [0xF0, 0x80, 0xF0, 0x10, 0xF0], // 5
+    [0xF0, 0x80, 0xF0, 0x90, 0xF0], // 6
+    [0xF0, 0x10, 0x20, 0x40, 0x40], // 7
+    [0xF0, 0x90, 0xF0, 0x90, 0xF0], // 8
+    [0xF0, 0x90, 0xF0, 0x10, 0xF0], // 9
+    [0xF0, 0x90, 0xF0, 0x90, 0x90], // A
+    [0xE0, 0x90, 0xE0, 0x90, 0xE0], // B
+    [0xF0, 0x80, 0x80, 0x80, 0xF0], // C
+    [0xE0, 0x90, 0x90, 0x90, 0xE0], // D
+    [0xF0, 0x80, 0xF0, 0x80, 0xF0], // E
+    [0xF0, 0x80, 0xF0, 0x80, 0x80], // F
+];
+
+// sizes in window pixels
+const CELL: u32 = 28;
+const GAP: u32 = 4;
+const MARGIN: u32 = 10;
+const GLYPH_PIXEL: u32 = 3;
+
+// translucent so the game stays visible underneath
+const IDLE_COLOR: Color = Color::RGBA(80, 80, 80, 160);
+const PRESSED_COLOR: Color = Color::RGBA(230, 160, 40, 220);
+const LABEL_COLOR: Color = Color::RGBA(255, 255, 255, 230);
+
+// draw the keypad in the bottom right corner, highlighting the keys that are held down
+pub fn draw_keypad(emu: &Emu, canvas: &mut Canvas<Window>, window_width: u32, window_height: u32) {
+    let size = 4 * CELL + 3 * GAP;
+    let left = window_width.saturating_sub(size + MARGIN) as i32;
+    let top = window_height.saturating_sub(size + MARGIN) as i32;
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    for (row, keys) in KEYPAD.iter().enumerate() {
+        for (col, key) in keys.iter().enumerate() {
+            let x = left + (col as u32 * (CELL + GAP)) as i32;
+            let y = top + (row as u32 * (CELL + GAP)) as i32;
+
+            canvas.set_draw_color(if emu.is_key_pressed(*key) {
+                PRESSED_COLOR
+            } else {
+                IDLE_COLOR
+            });
+            canvas.fill_rect(Rect::new(x, y, CELL, CELL)).unwrap();
+
+            draw_glyph(canvas, *key, x, y);
+        }
+    }
+    canvas.set_blend_mode(BlendMode::None);
+}
+
+// hex digit centered in the cell at (x, y)
+fn draw_glyph(canvas: &mut Canvas<Window>, digit: usize, x: i32, y: i32) {
+    let x = x + ((CELL - 4 * GLYPH_PIXEL) / 2) as i32;
+    let y = y + ((CELL - 5 * GLYPH_PIXEL) / 2) as i32;
+
+    canvas.set_draw_color(LABEL_COLOR);
+    for (row, bits) in GLYPHS[digit].iter().enumerate() {
+        for col in 0..4 {
+            if bits & (0x80 >> col) != 0 {
+                let rect = Rect::new(
+                    x + (col * GLYPH_PIXEL) as i32,
+                    y + (row as u32 * GLYPH_PIXEL) as i32,
+                    GLYPH_PIXEL,
+                    GLYPH_PIXEL,
+                );
+                canvas.fill_rect(rect).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use gif::{Encoder, EncodingError, Frame, Repeat};
+
+use std::fs::File;
+
+// cap recordings at ~20 seconds so a forgotten recording can't eat all memory
+const MAX_FRAMES: usize = 600;
+
+// gif delays are in 1/100ths of a second and most viewers bump anything under 2 up to 10
+// so only every other 60 Hz frame is kept and each is shown for 3/100ths (~30 fps)
+const FRAME_SKIP: usize = 2;
+const FRAME_DELAY: u16 = 3;
+
+// fastest colour quantization; frames only ever contain two colours anyway
+const QUANTIZE_SPEED: i32 = 30;
+
+// collects rendered frames in memory and encodes them to a gif once recording stops
+pub struct GifRecorder {
+    // unscaled RGBA frames (see `Emu::display_as_rgba`) and their width in pixels
+    // the width changes when SUPER-CHIP switches resolution mid-recording
+    frames: Vec<(usize, Vec<u8>)>,
+    // every frame seen so far, including skipped ones
+    seen: usize,
+}
+
+impl GifRecorder {
+    pub fn new() -> Self {
+        Self {
+            frames: Vec::new(),
+            seen: 0,
+        }
+    }
+
+    // append the latest rendered frame; returns false once the recording is full
+    pub fn push(&mut self, width: usize, rgba: Vec<u8>) -> bool {
+        if self.seen.is_multiple_of(FRAME_SKIP) {
+            self.frames.push((width, rgba));
+        }
+        self.seen += 1;
+
+        self.frames.len() < MAX_FRAMES
+    }
+
+    // encode every recorded frame scaled to fill `width` x `height` and write it to `path`
+    pub fn save(&self, path: &str, width: usize, height: usize) -> Result<(), EncodingError> {
+        let file = File::create(path)?;
+        let mut encoder = Encoder::new(file, width as u16, height as u16, &[])?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        for (frame_width, rgba) in self.frames.iter() {
+            let mut pixels = resize(rgba, *frame_width, width, height);
+            let mut frame =
+                Frame::from_rgba_speed(width as u16, height as u16, &mut pixels, QUANTIZE_SPEED);
+            frame.delay = FRAME_DELAY;
+            encoder.write_frame(&frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+// nearest-neighbour resample of a `width` pixel wide RGBA frame to exactly `out_width` x `out_height`
+// lores and hires frames both have to fill the same gif, and the size isn't always a whole multiple
+fn resize(rgba: &[u8], width: usize, out_width: usize, out_height: usize) -> Vec<u8> {
+    let height = rgba.len() / 4 / width;
+    let mut scaled = Vec::with_capacity(out_width * out_height * 4);
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let i = (x * width / out_width + width * (y * height / out_height)) * 4;
+            scaled.extend_from_slice(&rgba[i..i + 4]);
+        }
+    }
+
+    scaled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+
+    // a frame with only the top left pixel lit
+    fn frame(width: usize, height: usize) -> Vec<u8> {
+        let mut rgba = vec![0; width * height * 4];
+        rgba[..4].copy_from_slice(&[255, 255, 255, 255]);
+        rgba
+    }
+
+    #[test]
+    fn resize_fills_the_output_exactly() {
+        // 960 / 128 isn't a whole number, which is what used to break hires frames
+        let scaled = resize(&frame(128, 64), 128, 960, 480);
+        assert_eq!(scaled.len(), 960 * 480 * 4);
+        assert_eq!(scaled[..4], [255, 255, 255, 255]);
+        assert_eq!(scaled[960 * 4 * 479..960 * 4 * 479 + 4], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn saves_lores_and_hires_frames() {
+        let mut recorder = GifRecorder::new();
+        // frames are only kept every `FRAME_SKIP`, so push each one that many times
+        for _ in 0..FRAME_SKIP {
+            recorder.push(64, frame(64, 32));
+        }
+        for _ in 0..FRAME_SKIP {
+            recorder.push(128, frame(128, 64));
+        }
+
+        let path = env::temp_dir().join(format!("chip8-recorder-test-{}.gif", std::process::id()));
+        let path = path.to_str().unwrap();
+        recorder.save(path, 960, 480).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(path).unwrap())
+            .unwrap();
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (960, 480));
+            frames += 1;
+        }
+        fs::remove_file(path).unwrap();
+        assert_eq!(frames, 2);
+    }
+}
@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+// averaging over a whole second keeps the numbers readable instead of jittering every frame
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+// frames and instructions per second for `--stats`
+pub struct FrameStats {
+    since: Instant,
+    frames: u32,
+    cycles: u64,
+}
+
+impl FrameStats {
+    // `cycles` is the emulator's current `cycle_count`
+    pub fn new(cycles: u64) -> Self {
+        Self {
+            since: Instant::now(),
+            frames: 0,
+            cycles,
+        }
+    }
+
+    // count a presented frame; once a second returns "60 FPS, 600 IPS" for the window title
+    pub fn frame(&mut self, cycles: u64) -> Option<String> {
+        self.frames += 1;
+        let elapsed = self.since.elapsed();
+        if elapsed < UPDATE_INTERVAL {
+            return None;
+        }
+
+        // the count starts over when the game is reset (reloaded or picked from the menu); then
+        // everything since the reset is as close as it gets
+        let ran = cycles.checked_sub(self.cycles).unwrap_or(cycles);
+        let seconds = elapsed.as_secs_f64();
+        let text = format!(
+            "{:.0} FPS, {:.0} IPS",
+            self.frames as f64 / seconds,
+            ran as f64 / seconds
+        );
+        *self = Self::new(cycles);
+        Some(text)
+    }
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+
+// how often the file is checked; stat-ing every frame is wasteful
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// polls a ROM file's modification time so it can be reloaded when it's rebuilt
+pub struct RomWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl RomWatcher {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: String::from(path),
+            modified: modified(path),
+            last_check: Instant::now(),
+        }
+    }
+
+    // whether the file changed since it was last seen; safe to call every frame
+    pub fn changed(&mut self) -> bool {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified(&self.path);
+        // a missing file (e.g. mid-rebuild) isn't a change; wait for it to come back
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+
+        self.modified = modified;
+        true
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
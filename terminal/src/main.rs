@@ -0,0 +1,188 @@
+use chip8_core::*;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+// arbitrary value; CHIP-8 spec doesn't say anything about how fast clock speed should be
+const TICKS_PER_FRAME: usize = 10;
+
+// timers and the screen both run at 60 Hz
+const FRAME_TIME: Duration = Duration::from_micros(16_667);
+
+// terminals only report key presses (plus auto-repeat), never releases
+// so a key is treated as held for this many frames after its last press
+const KEY_HOLD_FRAMES: u8 = 6;
+
+fn main() {
+    let args: Vec<_> = env::args().collect();
+    if args.len() != 2 {
+        println!("Usage: cargo run path/to/game");
+        return;
+    }
+
+    // instantiate emulation object
+    let mut chip8 = Emu::new();
+
+    let buffer = match read_rom(&args[1]) {
+        Ok(buffer) => buffer,
+        Err(err) => {
+            println!("unable to open {}: {}", args[1], err);
+            return;
+        }
+    };
+    if let Err(err) = chip8.load(&buffer) {
+        println!("{}", Chip8Error::from(err));
+        return;
+    }
+
+    // take over the terminal, making sure it's handed back even if the emulator stops with an error
+    terminal::enable_raw_mode().expect("unable to enable raw mode");
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All)).unwrap();
+
+    let result = run(&mut chip8, &mut stdout);
+
+    execute!(stdout, Show, LeaveAlternateScreen).unwrap();
+    terminal::disable_raw_mode().unwrap();
+
+    if let Err(err) = result {
+        println!("{}", err);
+    }
+}
+
+// game loop; returns once the player quits or the emulator hits an error
+fn run(chip8: &mut Emu, out: &mut impl Write) -> Result<(), String> {
+    // frames left before each CHIP-8 key is released
+    let mut held = [0u8; 16];
+
+    loop {
+        let frame_start = Instant::now();
+
+        // drain every pending event without blocking
+        while event::poll(Duration::ZERO).map_err(|e| e.to_string())? {
+            if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+                if is_quit(&key) {
+                    return Ok(());
+                }
+
+                if key.kind != KeyEventKind::Release {
+                    if let Some(k) = key2btn(key.code) {
+                        held[k] = KEY_HOLD_FRAMES;
+                        chip8.keypress(k, true);
+                    }
+                }
+            }
+        }
+
+        // clock speed is 10 ticks per frame (arbitrary value); timers tick once per frame
+        let frame = chip8
+            .run_frame(TICKS_PER_FRAME)
+            .map_err(|e| e.to_string())?;
+
+        // let go of any key that hasn't been repeated recently
+        for (k, frames) in held.iter_mut().enumerate() {
+            if *frames > 0 {
+                *frames -= 1;
+                if *frames == 0 {
+                    chip8.keypress(k, false);
+                }
+            }
+        }
+
+        // terminals are slow to redraw so skip frames where nothing changed
+        if frame.screen_changed {
+            draw_screen(chip8, out).map_err(|e| e.to_string())?;
+        }
+
+        if let Some(rest) = FRAME_TIME.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+}
+
+// read the whole ROM file into memory
+fn read_rom(path: &str) -> io::Result<Vec<u8>> {
+    let mut rom = File::open(path)?;
+    let mut buffer = Vec::new();
+    rom.read_to_end(&mut buffer)?;
+
+    Ok(buffer)
+}
+
+// escape or ctrl-c; raw mode swallows the usual SIGINT
+fn is_quit(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Esc
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+// draw loop
+// each terminal row covers two CHIP-8 rows: the upper half block is the top pixel, its background the bottom
+fn draw_screen(emu: &Emu, out: &mut impl Write) -> io::Result<()> {
+    let width = emu.display_width();
+    let height = emu.display_height();
+
+    for row in 0..height / 2 {
+        let mut line = String::with_capacity(width * 3);
+        for x in 0..width {
+            let top = emu.pixel(x, row * 2);
+            let bottom = emu.pixel(x, row * 2 + 1);
+
+            line.push(match (top, bottom) {
+                (Some(true), Some(true)) => '█',
+                (Some(true), _) => '▀',
+                (_, Some(true)) => '▄',
+                _ => ' ',
+            });
+        }
+
+        queue!(out, MoveTo(0, row as u16), Print(line))?;
+    }
+
+    out.flush()
+}
+
+// key mapper
+fn key2btn(key: KeyCode) -> Option<usize> {
+    //  keyboard             CHIP-8
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | 1 | 2 | 3 | 4 |    | 1 | 2 | 3 | C |
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | Q | W | E | R |    | 4 | 5 | 6 | D |
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | A | S | D | F |    | 7 | 8 | 9 | E |
+    //  +---+---+---+---+    +---+---+---+---+
+    //  | Z | X | C | V |    | A | 0 | B | F |
+    //  +---+---+---+---+    +---+---+---+---+
+
+    let KeyCode::Char(c) = key else {
+        return None;
+    };
+
+    match c.to_ascii_lowercase() {
+        '1' => Some(0x1),
+        '2' => Some(0x2),
+        '3' => Some(0x3),
+        '4' => Some(0xC),
+        'q' => Some(0x4),
+        'w' => Some(0x5),
+        'e' => Some(0x6),
+        'r' => Some(0xD),
+        'a' => Some(0x7),
+        's' => Some(0x8),
+        'd' => Some(0x9),
+        'f' => Some(0xE),
+        'z' => Some(0xA),
+        'x' => Some(0x0),
+        'c' => Some(0xB),
+        'v' => Some(0xF),
+        _ => None,
+    }
+}
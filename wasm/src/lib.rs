@@ -36,8 +36,12 @@ impl EmuWasm {
     }
 
     #[wasm_bindgen]
-    pub fn tick(&mut self) {
-        self.chip8.tick();
+    pub fn tick(&mut self) -> Result<(), JsValue> {
+        // surface emulation errors to javascript as exceptions
+        self.chip8
+            .tick()
+            .map(|_| ())
+            .map_err(|err| JsValue::from_str(&err.to_string()))
     }
 
     #[wasm_bindgen]
@@ -45,6 +49,15 @@ impl EmuWasm {
         self.chip8.tick_timers();
     }
 
+    // run a whole frame (see `Emu::run_frame`); returns whether the screen needs redrawing
+    #[wasm_bindgen]
+    pub fn run_frame(&mut self, ticks_per_frame: usize) -> Result<bool, JsValue> {
+        self.chip8
+            .run_frame(ticks_per_frame)
+            .map(|frame| frame.screen_changed)
+            .map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
     #[wasm_bindgen]
     pub fn reset(&mut self) {
         self.chip8.reset();
@@ -59,23 +72,24 @@ impl EmuWasm {
     }
 
     #[wasm_bindgen]
-    pub fn load_game(&mut self, data: Uint8Array) {
-        self.chip8.load(&data.to_vec());
+    pub fn load_game(&mut self, data: Uint8Array) -> Result<(), JsValue> {
+        self.chip8
+            .load(&data.to_vec())
+            .map_err(|err| JsValue::from_str(&err.to_string()))
     }
 
     #[wasm_bindgen]
     pub fn draw_screen(&mut self, scale: usize) {
         let display = self.chip8.get_display();
-        for i in 0..(SCREEN_WIDTH * SCREEN_HEIGHT) {
-            if display[i] {
-                let x = i % SCREEN_WIDTH;
-                let y = i / SCREEN_WIDTH;
-                self.ctx.fill_rect(
-                    (x * scale) as f64,
-                    (y * scale) as f64,
-                    scale as f64,
-                    scale as f64,
-                );
+        let width = self.chip8.display_width();
+        // `scale` is given for lores; SUPER-CHIP hires pixels are drawn proportionally smaller
+        let size = (scale * SCREEN_WIDTH) as f64 / width as f64;
+        for (i, pixel) in display.iter().enumerate() {
+            if *pixel {
+                let x = i % width;
+                let y = i / width;
+                self.ctx
+                    .fill_rect(x as f64 * size, y as f64 * size, size, size);
             }
         }
     }
@@ -49,8 +49,13 @@ async function run() {
         const rom = new Uint8Array(buffer);
         chip8.reset();
 
-        // send to .wasm
-        chip8.load_game(rom);
+        // send to .wasm; a bad ROM is reported instead of starting the loop
+        try {
+          chip8.load_game(rom);
+        } catch (err) {
+          alert(err);
+          return;
+        }
 
         // start main loop
         mainloop(chip8);
@@ -62,19 +67,26 @@ async function run() {
 }
 
 function mainloop(chip8) {
-  // only draw every few ticks
-  for (let i = 0; i < TICKS_PER_FRAME; i++) {
-    chip8.tick();
+  // emulation errors (e.g. an unknown opcode) stop the game
+  let changed;
+  try {
+    changed = chip8.run_frame(TICKS_PER_FRAME);
+  } catch (err) {
+    animationFrame = 0;
+    alert(err);
+    return;
   }
-  chip8.tick_timers();
 
-  // clear the canvas before drawing
-  ctx.fillStyle = "black";
-  ctx.fillRect(0, 0, WIDTH * SCALE, HEIGHT * SCALE);
+  // only draw every few ticks, and only when something changed
+  if (changed) {
+    // clear the canvas before drawing
+    ctx.fillStyle = "black";
+    ctx.fillRect(0, 0, WIDTH * SCALE, HEIGHT * SCALE);
 
-  // set the draw color back to white before drawing a frame
-  ctx.fillStyle = "white";
-  chip8.draw_screen(SCALE);
+    // set the draw color back to white before drawing a frame
+    ctx.fillStyle = "white";
+    chip8.draw_screen(SCALE);
+  }
 
   animationFrame = window.requestAnimationFrame(() => mainloop(chip8));
 }